@@ -1,7 +1,10 @@
 use std::future::Future;
 
 use dale::{filters::One, IntoOutcome, Outcome, Service, ServiceExt};
-use dale_http::{Body, Request, RequestExt};
+use dale_http::{
+    filters::{param, Param},
+    Body, Request,
+};
 
 use crate::Data;
 
@@ -29,23 +32,9 @@ use super::model::{Model, Query};
 //     }
 // }
 
-pub fn id<B: Send>(
-    path: impl ToString,
-) -> impl Service<
-    Request<B>,
-    Output = Outcome<(Request<B>, One<String>), dale_http::Error, Request<B>>,
-    Future = impl Future + Send,
-> + Clone {
-    let path = path.to_string();
-    move |req: Request<B>| {
-        let id = req.params().get(&path).map(|m| m.to_string());
-        async move {
-            match id {
-                Some(id) => Outcome::Success((req, (id.to_string(),))),
-                None => Outcome::Next(req),
-            }
-        }
-    }
+/// The route parameter `path`, percent-decoded.
+pub fn id(path: impl ToString) -> Param<String> {
+    param(path.to_string())
 }
 
 pub fn data<M, B>() -> impl Service<
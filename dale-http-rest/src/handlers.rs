@@ -1,5 +1,5 @@
-use dale::{boxed::BoxFuture, fail, filters::One, next, success, Outcome, Service};
-use dale_http::{Body, Request, RequestExt};
+use dale::{boxed::BoxFuture, fail, filters::One, success, try_outcome, Outcome, Service};
+use dale_http::{filters::param, Body, Request};
 
 use crate::{
     method::RestMethod,
@@ -150,14 +150,11 @@ where
 
     fn call(&self, req: Request<B>) -> Self::Future {
         let model = self.model.clone();
-        let key = self.key.clone();
+        let id = param::<String>(self.key.clone());
         Box::pin(async move {
-            let id = match req.params().get(&key) {
-                Some(id) => id,
-                None => return next!(req),
-            };
+            let (req, (id,)) = try_outcome!(id.call(req).await);
 
-            let future = model.get(id);
+            let future = model.get(&id);
             let ret = fail!(future.await.map_err(dale_http::Error::new));
             success!((req, (ret,)))
         })
@@ -3,7 +3,7 @@ use dale::{
 };
 use dale_http::{
     reply,
-    router::{AsSegments, IntoIter, Router, Routing},
+    router::{AsSegments, IntoIter, RouteInfo, RouteTable, Router, Routing},
     Body, Request,
 };
 
@@ -39,6 +39,22 @@ impl<B: Send> RestRouter<B> {
         Ok(self)
     }
 
+    /// See [`Router::name`].
+    pub fn name(&mut self, name: impl Into<String>) -> &mut Self {
+        self.router.name(name);
+        self
+    }
+
+    /// See [`Router::routes`].
+    pub fn routes(&self) -> impl Iterator<Item = &RouteInfo> {
+        self.router.routes()
+    }
+
+    /// See [`Router::route_table`].
+    pub fn route_table(&self) -> RouteTable<'_> {
+        self.router.route_table()
+    }
+
     pub fn extend<M: Model>(&mut self, routes: RouteSet<M>)
     where
         B: Body + 'static + Send,
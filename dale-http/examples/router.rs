@@ -1,4 +1,4 @@
-use dale::{IntoService, ServiceExt};
+use dale::{Describe, IntoService, ServiceExt};
 use dale_http::{
     router::{Router, Routing},
     Request, RequestExt,
@@ -22,7 +22,11 @@ async fn main() -> std::result::Result<(), Box<dyn std::error::Error + Send + Sy
         })?
         .get("/simple", filters::url().map(|u| "Hello, Simple!"))?;
 
-    let service = dale_http::hyper::make(router.into_service()?);
+    let service = router.into_service()?;
+
+    println!("{}", service.describe());
+
+    let service = dale_http::hyper::make(service);
 
     Server::bind(&addr).serve(service).await?;
 
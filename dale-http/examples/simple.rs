@@ -1,13 +1,15 @@
-use dale_http::prelude::*;
-use hyper::Server;
+use dale_http::{prelude::*, server};
+use std::time::Duration;
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let addr = ([127, 0, 0, 1], 3000).into();
+    let service = dale::filters::any().map(|| "Hello, World!");
 
-    let service = dale_http::hyper::make(dale::filters::any().map(|| "Hello, World!"));
-
-    Server::bind(&addr).serve(service).await?;
+    server::serve(([127, 0, 0, 1], 3000), service)
+        .with_signal()
+        .drain_timeout(Duration::from_secs(10))
+        .run()
+        .await?;
 
     Ok(())
 }
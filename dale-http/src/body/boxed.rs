@@ -0,0 +1,190 @@
+use super::{Empty, Full, StreamBody};
+use crate::{error::BoxError, Body};
+use bytes::{Buf, Bytes};
+#[cfg(feature = "stream")]
+use futures_core::Stream;
+use http::HeaderMap;
+use http_body::{Body as HttpBody, SizeHint};
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+type DynBody = dyn HttpBody<Data = Bytes, Error = BoxError> + Send + Sync;
+
+/// A type-erased body, so handlers can return different body types.
+pub struct BoxBody {
+    inner: Pin<Box<DynBody>>,
+}
+
+impl BoxBody {
+    pub fn new<B>(body: B) -> BoxBody
+    where
+        B: HttpBody + Send + Sync + 'static,
+        B::Error: Into<BoxError>,
+    {
+        BoxBody {
+            inner: Box::pin(MapBody { body }),
+        }
+    }
+}
+
+impl Default for BoxBody {
+    fn default() -> Self {
+        BoxBody::new(Empty)
+    }
+}
+
+impl fmt::Debug for BoxBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BoxBody").finish()
+    }
+}
+
+impl HttpBody for BoxBody {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        self.inner.as_mut().poll_data(cx)
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        self.inner.as_mut().poll_trailers(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl Body for BoxBody {
+    fn empty() -> Self {
+        BoxBody::new(Empty)
+    }
+
+    fn from_bytes(bytes: Vec<u8>) -> Self {
+        BoxBody::new(Full::new(bytes))
+    }
+
+    #[cfg(feature = "stream")]
+    fn from_stream<S, O, E>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<O, E>> + Send + 'static,
+        O: Into<Bytes> + 'static,
+        E: Into<Box<dyn std::error::Error + Send + Sync>> + 'static,
+    {
+        BoxBody::new(StreamBody::new(stream))
+    }
+}
+
+impl From<Full> for BoxBody {
+    fn from(body: Full) -> Self {
+        BoxBody::new(body)
+    }
+}
+
+impl From<Empty> for BoxBody {
+    fn from(body: Empty) -> Self {
+        BoxBody::new(body)
+    }
+}
+
+impl<S> From<StreamBody<S>> for BoxBody
+where
+    StreamBody<S>: HttpBody + Send + Sync + 'static,
+    <StreamBody<S> as HttpBody>::Error: Into<BoxError>,
+{
+    fn from(body: StreamBody<S>) -> Self {
+        BoxBody::new(body)
+    }
+}
+
+pin_project_lite::pin_project! {
+    struct MapBody<B> {
+        #[pin]
+        body: B,
+    }
+}
+
+impl<B> HttpBody for MapBody<B>
+where
+    B: HttpBody,
+    B::Error: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        match futures_core::ready!(self.project().body.poll_data(cx)) {
+            Some(Ok(mut data)) => Poll::Ready(Some(Ok(data.copy_to_bytes(data.remaining())))),
+            Some(Err(err)) => Poll::Ready(Some(Err(err.into()))),
+            None => Poll::Ready(None),
+        }
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        self.project().body.poll_trailers(cx).map_err(Into::into)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.body.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.body.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{common::ToBytes, Reply};
+    use http::Response;
+
+    #[tokio::test]
+    async fn test_box_body() {
+        let chunks = vec![Ok::<_, BoxError>("hello, "), Ok("world")];
+        let stream = futures_util::stream::iter(chunks);
+        let body = BoxBody::from(StreamBody::new(stream));
+        assert_eq!(ToBytes::new(body).await.unwrap(), "hello, world");
+
+        let resp: Response<BoxBody> = Full::from("full").into_response();
+        assert_eq!(resp.body().size_hint().exact(), Some(4));
+        assert_eq!(ToBytes::new(resp.into_body()).await.unwrap(), "full");
+
+        let resp: Response<BoxBody> = Empty.into_response();
+        assert!(resp.body().is_end_stream());
+    }
+
+    #[cfg(feature = "stream")]
+    #[tokio::test]
+    async fn test_full_body() {
+        assert!(Full::empty().is_end_stream());
+
+        let body = Full::from_bytes(b"full".to_vec());
+        assert_eq!(body.size_hint().exact(), Some(4));
+        assert_eq!(ToBytes::new(body).await.unwrap(), "full");
+
+        let chunks = vec![Ok::<_, BoxError>("hello, "), Ok("world")];
+        let body = Full::from_stream(futures_util::stream::iter(chunks));
+        assert_eq!(ToBytes::new(body).await.unwrap(), "hello, world");
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{Body, Reply};
+use bytes::Bytes;
+use http::{HeaderMap, Response};
+use http_body::{Body as HttpBody, SizeHint};
+use std::{
+    convert::Infallible,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// A body without any data.
+///
+/// Not a [`Body`]: it has nowhere to put the data of [`Body::from_bytes`].
+/// Use [`Full`](super::Full) where one is needed.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Empty;
+
+impl HttpBody for Empty {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        Poll::Ready(None)
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Poll::Ready(Ok(None))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        true
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        SizeHint::with_exact(0)
+    }
+}
+
+impl<B: Body> Reply<B> for Empty {
+    fn into_response(self) -> Response<B> {
+        Response::new(B::empty())
+    }
+}
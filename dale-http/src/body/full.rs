@@ -0,0 +1,178 @@
+#[cfg(feature = "stream")]
+use super::StreamBody;
+use crate::{error::BoxError, Body, Reply};
+use bytes::Bytes;
+#[cfg(feature = "stream")]
+use futures_core::Stream;
+use http::{HeaderMap, Response};
+use http_body::{Body as HttpBody, SizeHint};
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[cfg(feature = "stream")]
+type BoxStream = Pin<Box<dyn Stream<Item = Result<Bytes, BoxError>> + Send>>;
+
+/// A body consisting of a single chunk. Built with [`Body::from_stream`], it
+/// yields the chunks of the stream instead.
+#[derive(Default)]
+pub struct Full {
+    inner: Inner,
+}
+
+enum Inner {
+    Chunk(Option<Bytes>),
+    #[cfg(feature = "stream")]
+    Stream(StreamBody<BoxStream>),
+}
+
+impl Default for Inner {
+    fn default() -> Self {
+        Inner::Chunk(None)
+    }
+}
+
+impl Full {
+    pub fn new(data: impl Into<Bytes>) -> Full {
+        let data = data.into();
+        Full {
+            inner: Inner::Chunk(if data.is_empty() { None } else { Some(data) }),
+        }
+    }
+}
+
+impl fmt::Debug for Full {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.inner {
+            Inner::Chunk(data) => f.debug_struct("Full").field("data", data).finish(),
+            #[cfg(feature = "stream")]
+            Inner::Stream(_) => f.debug_struct("Full").finish_non_exhaustive(),
+        }
+    }
+}
+
+impl HttpBody for Full {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    #[cfg_attr(not(feature = "stream"), allow(unused_variables))]
+    fn poll_data(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        match &mut self.get_mut().inner {
+            Inner::Chunk(data) => Poll::Ready(data.take().map(Ok)),
+            #[cfg(feature = "stream")]
+            Inner::Stream(stream) => Pin::new(stream).poll_data(cx),
+        }
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Poll::Ready(Ok(None))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match &self.inner {
+            Inner::Chunk(data) => data.is_none(),
+            #[cfg(feature = "stream")]
+            Inner::Stream(_) => false,
+        }
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        match &self.inner {
+            Inner::Chunk(data) => {
+                SizeHint::with_exact(data.as_ref().map(|data| data.len() as u64).unwrap_or(0))
+            }
+            #[cfg(feature = "stream")]
+            Inner::Stream(_) => SizeHint::default(),
+        }
+    }
+}
+
+impl Body for Full {
+    fn empty() -> Self {
+        Full::default()
+    }
+
+    fn from_bytes(bytes: Vec<u8>) -> Self {
+        Full::new(bytes)
+    }
+
+    #[cfg(feature = "stream")]
+    fn from_stream<S, O, E>(stream: S) -> Self
+    where
+        S: Stream<Item = Result<O, E>> + Send + 'static,
+        O: Into<Bytes> + 'static,
+        E: Into<BoxError> + 'static,
+    {
+        Full {
+            inner: Inner::Stream(StreamBody::new(Box::pin(Chunks { stream }))),
+        }
+    }
+}
+
+#[cfg(feature = "stream")]
+pin_project_lite::pin_project! {
+    /// Converts the chunks and errors of a stream.
+    struct Chunks<S> {
+        #[pin]
+        stream: S,
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<S, O, E> Stream for Chunks<S>
+where
+    S: Stream<Item = Result<O, E>>,
+    O: Into<Bytes>,
+    E: Into<BoxError>,
+{
+    type Item = Result<Bytes, BoxError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.project()
+            .stream
+            .poll_next(cx)
+            .map(|ret| ret.map(|ret| ret.map(Into::into).map_err(Into::into)))
+    }
+}
+
+impl From<Bytes> for Full {
+    fn from(data: Bytes) -> Self {
+        Full::new(data)
+    }
+}
+
+impl From<Vec<u8>> for Full {
+    fn from(data: Vec<u8>) -> Self {
+        Full::new(data)
+    }
+}
+
+impl From<String> for Full {
+    fn from(data: String) -> Self {
+        Full::new(data)
+    }
+}
+
+impl From<&'static str> for Full {
+    fn from(data: &'static str) -> Self {
+        Full::new(data)
+    }
+}
+
+impl<B: Body> Reply<B> for Full {
+    fn into_response(self) -> Response<B> {
+        Response::new(match self.inner {
+            Inner::Chunk(data) => B::from_bytes(data.map(|data| data.to_vec()).unwrap_or_default()),
+            #[cfg(feature = "stream")]
+            Inner::Stream(stream) => B::from_stream(stream.into_inner()),
+        })
+    }
+}
@@ -1,3 +1,10 @@
+mod boxed;
+mod empty;
+mod full;
+mod stream;
+
+pub use self::{boxed::*, empty::*, full::*, stream::*};
+
 use bytes::Buf;
 #[cfg(feature = "stream")]
 use bytes::Bytes;
@@ -0,0 +1,80 @@
+use bytes::Bytes;
+use futures_core::{ready, Stream};
+use http::HeaderMap;
+use http_body::Body as HttpBody;
+use std::{
+    pin::Pin,
+    sync::{Mutex, PoisonError},
+    task::{Context, Poll},
+};
+
+/// A body yielding the chunks of a stream.
+///
+/// Not a [`Body`](crate::Body): it can't be built from bytes or from nothing
+/// for an arbitrary stream type. Box it into a
+/// [`BoxBody`](crate::body::BoxBody) where one is needed.
+pub struct StreamBody<S> {
+    // Only ever reached through `&mut self`, where `Mutex::get_mut` doesn't
+    // lock. The mutex makes the body `Sync` for any `Send` stream.
+    stream: Mutex<Pin<Box<S>>>,
+}
+
+impl<S> StreamBody<S> {
+    pub fn new(stream: S) -> StreamBody<S> {
+        StreamBody {
+            stream: Mutex::new(Box::pin(stream)),
+        }
+    }
+
+    #[cfg(feature = "stream")]
+    pub(crate) fn into_inner(self) -> Pin<Box<S>> {
+        self.stream
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<S, O, E> HttpBody for StreamBody<S>
+where
+    S: Stream<Item = Result<O, E>>,
+    O: Into<Bytes>,
+{
+    type Data = Bytes;
+    type Error = E;
+
+    fn poll_data(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        let stream = self
+            .get_mut()
+            .stream
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        match ready!(stream.as_mut().poll_next(cx)) {
+            Some(Ok(data)) => Poll::Ready(Some(Ok(data.into()))),
+            Some(Err(err)) => Poll::Ready(Some(Err(err))),
+            None => Poll::Ready(None),
+        }
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Poll::Ready(Ok(None))
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<S, O, E, B> crate::Reply<B> for StreamBody<S>
+where
+    S: Stream<Item = Result<O, E>> + Send + 'static,
+    O: Into<Bytes> + 'static,
+    E: Into<crate::error::BoxError> + 'static,
+    B: crate::Body,
+{
+    fn into_response(self) -> http::Response<B> {
+        http::Response::new(B::from_stream(self.into_inner()))
+    }
+}
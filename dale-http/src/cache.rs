@@ -0,0 +1,394 @@
+//! Response caching for HTTP services.
+//!
+//! Only `GET` and `HEAD` requests answered with `200 OK` are cached. The
+//! response decides its own lifetime through `Cache-Control` (`s-maxage`,
+//! then `max-age`, then the configured fallback) and is skipped when marked
+//! `no-store`, `no-cache` or `private`, when it sets cookies, or when it
+//! varies on `*`. Headers named in `Vary` must match for a hit. Requests
+//! carrying `Cache-Control: no-cache` or `no-store` bypass the cache.
+use crate::{
+    common::SharedResponse,
+    error::{BoxError, Error},
+    Body, Outcome, Reply,
+};
+use dale::{boxed::BoxFuture, CacheHandle, CacheStore, IntoOutcome, Middleware, Service};
+use http::{
+    header::{self, HeaderName},
+    HeaderMap, HeaderValue, Method, Request, Response, StatusCode, Uri,
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+pub type CacheKey = (Method, Uri);
+
+#[derive(Debug, Clone)]
+pub struct Cache {
+    capacity: usize,
+    ttl: Option<Duration>,
+    handle: CacheHandle<CacheKey>,
+}
+
+impl Cache {
+    pub fn new() -> Cache {
+        Cache {
+            capacity: 1024,
+            ttl: None,
+            handle: CacheHandle::new(),
+        }
+    }
+
+    /// The maximum number of URIs kept, per wrapped service.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Lifetime for responses without `max-age`. Without it, such responses
+    /// aren't cached.
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    pub fn handle(&self) -> CacheHandle<CacheKey> {
+        self.handle.clone()
+    }
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Cache::new()
+    }
+}
+
+impl<B, T> Middleware<Request<B>, T> for Cache
+where
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    T: Service<Request<B>>,
+    T::Future: Send + 'static,
+    <T::Output as IntoOutcome<Request<B>>>::Success: Reply<B> + Send,
+    <T::Output as IntoOutcome<Request<B>>>::Failure: Into<Error> + Send,
+{
+    type Service = CacheService<T>;
+
+    fn wrap(&self, service: T) -> Self::Service {
+        let store = Arc::new(Mutex::new(CacheStore::new(self.capacity)));
+        self.handle.register(&store);
+
+        CacheService {
+            service,
+            store,
+            ttl: self.ttl,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CacheService<T> {
+    service: T,
+    store: Arc<Mutex<CacheStore<CacheKey, Vec<Variant>>>>,
+    ttl: Option<Duration>,
+}
+
+impl<B, T> Service<Request<B>> for CacheService<T>
+where
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    T: Service<Request<B>>,
+    T::Future: Send + 'static,
+    <T::Output as IntoOutcome<Request<B>>>::Success: Reply<B> + Send,
+    <T::Output as IntoOutcome<Request<B>>>::Failure: Into<Error> + Send,
+{
+    type Output = Outcome<B>;
+
+    type Future = BoxFuture<'static, Self::Output>;
+
+    fn call(&self, req: Request<B>) -> Self::Future {
+        let key = match cache_key(&req) {
+            Some(key) => key,
+            None => {
+                let future = self.service.call(req);
+                return Box::pin(async move {
+                    match future.await.into_outcome() {
+                        dale::Outcome::Success(reply) => {
+                            dale::Outcome::Success(reply.into_response())
+                        }
+                        dale::Outcome::Failure(err) => dale::Outcome::Failure(err.into()),
+                        dale::Outcome::Next(req) => dale::Outcome::Next(req),
+                    }
+                });
+            }
+        };
+
+        let hit = self.store.lock().unwrap().get(&key).and_then(|variants| {
+            variants
+                .iter()
+                .find(|variant| variant.matches(req.headers()))
+                .map(|variant| variant.response.clone())
+        });
+
+        if let Some(hit) = hit {
+            return Box::pin(async move { dale::Outcome::Success(hit.to_response()) });
+        }
+
+        let headers = req.headers().clone();
+        let future = self.service.call(req);
+        let store = self.store.clone();
+        let ttl = self.ttl;
+
+        Box::pin(async move {
+            let resp = match future.await.into_outcome() {
+                dale::Outcome::Success(reply) => reply.into_response(),
+                dale::Outcome::Failure(err) => return dale::Outcome::Failure(err.into()),
+                dale::Outcome::Next(req) => return dale::Outcome::Next(req),
+            };
+
+            let (ttl, vary) = match cacheable(&headers, &resp, ttl) {
+                Some(ret) => ret,
+                None => return dale::Outcome::Success(resp),
+            };
+
+            let shared = match SharedResponse::buffer(resp).await {
+                Ok(shared) => shared,
+                Err(err) => return dale::Outcome::Failure(err),
+            };
+
+            let variant = Variant {
+                vary: vary
+                    .into_iter()
+                    .map(|name| {
+                        let value = headers.get(&name).cloned();
+                        (name, value)
+                    })
+                    .collect(),
+                expires: Instant::now() + ttl,
+                response: shared.clone(),
+            };
+
+            let mut store = store.lock().unwrap();
+            let now = Instant::now();
+            let mut variants = store.remove(&key).unwrap_or_default();
+            variants.retain(|next| next.expires > now && next.vary != variant.vary);
+            variants.push(variant);
+
+            if let Some(expires) = variants.iter().map(|variant| variant.expires).max() {
+                store.insert(key, variants, expires - now);
+            }
+
+            dale::Outcome::Success(shared.to_response())
+        })
+    }
+}
+
+impl<T> dale::Describe for CacheService<T>
+where
+    T: dale::Describe,
+{
+    fn describe(&self) -> dale::Description {
+        dale::Description::new("cache").with_child(self.service.describe())
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Variant {
+    vary: Vec<(HeaderName, Option<HeaderValue>)>,
+    expires: Instant,
+    response: SharedResponse,
+}
+
+impl Variant {
+    fn matches(&self, headers: &HeaderMap) -> bool {
+        self.expires > Instant::now()
+            && self
+                .vary
+                .iter()
+                .all(|(name, value)| headers.get(name) == value.as_ref())
+    }
+}
+
+#[derive(Debug, Default)]
+struct CacheControl {
+    no_store: bool,
+    no_cache: bool,
+    private: bool,
+    public: bool,
+    max_age: Option<u64>,
+    s_maxage: Option<u64>,
+}
+
+impl CacheControl {
+    fn parse(headers: &HeaderMap) -> CacheControl {
+        let mut cc = CacheControl::default();
+
+        let directives = headers
+            .get_all(header::CACHE_CONTROL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','));
+
+        for directive in directives {
+            let (name, value) = match directive.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (directive.trim(), None),
+            };
+
+            match name.to_ascii_lowercase().as_str() {
+                "no-store" => cc.no_store = true,
+                "no-cache" => cc.no_cache = true,
+                "private" => cc.private = true,
+                "public" => cc.public = true,
+                "max-age" => cc.max_age = value.and_then(|v| v.parse().ok()),
+                "s-maxage" => cc.s_maxage = value.and_then(|v| v.parse().ok()),
+                _ => {}
+            }
+        }
+
+        cc
+    }
+}
+
+fn cache_key<B>(req: &Request<B>) -> Option<CacheKey> {
+    if req.method() != Method::GET && req.method() != Method::HEAD {
+        return None;
+    }
+
+    let cc = CacheControl::parse(req.headers());
+    if cc.no_store || cc.no_cache {
+        return None;
+    }
+
+    Some((req.method().clone(), req.uri().clone()))
+}
+
+fn cacheable<B>(
+    req: &HeaderMap,
+    resp: &Response<B>,
+    ttl: Option<Duration>,
+) -> Option<(Duration, Vec<HeaderName>)> {
+    if resp.status() != StatusCode::OK || resp.headers().contains_key(header::SET_COOKIE) {
+        return None;
+    }
+
+    let cc = CacheControl::parse(resp.headers());
+    if cc.no_store || cc.no_cache || cc.private {
+        return None;
+    }
+
+    if req.contains_key(header::AUTHORIZATION) && !cc.public && cc.s_maxage.is_none() {
+        return None;
+    }
+
+    let mut vary = Vec::new();
+    for name in resp
+        .headers()
+        .get_all(header::VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+    {
+        if name == "*" {
+            return None;
+        }
+        vary.push(HeaderName::from_bytes(name.as_bytes()).ok()?);
+    }
+
+    let ttl = cc
+        .s_maxage
+        .or(cc.max_age)
+        .map(Duration::from_secs)
+        .or(ttl)
+        .filter(|ttl| !ttl.is_zero())?;
+
+    Some((ttl, vary))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cacheable() {
+        let resp = Response::builder()
+            .header("cache-control", "public, max-age=60")
+            .header("vary", "Accept-Encoding")
+            .body(())
+            .unwrap();
+
+        let (ttl, vary) = cacheable(&HeaderMap::new(), &resp, None).unwrap();
+        assert_eq!(ttl, Duration::from_secs(60));
+        assert_eq!(vary, vec![header::ACCEPT_ENCODING]);
+
+        let resp = Response::builder()
+            .header("cache-control", "max-age=60, private")
+            .body(())
+            .unwrap();
+        assert!(cacheable(&HeaderMap::new(), &resp, None).is_none());
+
+        let resp = Response::new(());
+        assert!(cacheable(&HeaderMap::new(), &resp, None).is_none());
+        assert!(cacheable(&HeaderMap::new(), &resp, Some(Duration::from_secs(1))).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_cache() {
+        use crate::{body::BoxBody, test::request};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = Cache::new().wrap({
+            let calls = calls.clone();
+            move |req: Request<BoxBody>| {
+                let calls = calls.clone();
+                async move {
+                    let n = calls.fetch_add(1, Ordering::SeqCst);
+                    let cache_control = match req.uri().path() {
+                        "/no-store" => "no-store",
+                        "/private" => "private, max-age=60",
+                        _ => "public, max-age=60",
+                    };
+                    let lang = req
+                        .headers()
+                        .get("accept-language")
+                        .map(|value| value.to_str().unwrap().to_string())
+                        .unwrap_or_default();
+
+                    Response::builder()
+                        .header("cache-control", cache_control)
+                        .header("vary", "accept-language")
+                        .body(BoxBody::from_bytes(format!("{} {}", n, lang).into_bytes()))
+                        .map_err(Error::new)
+                }
+            }
+        });
+
+        let send = |path: &'static str, lang: &'static str| {
+            let service = service.clone();
+            async move {
+                request()
+                    .path(path)
+                    .header("accept-language", lang)
+                    .send(&service)
+                    .await
+                    .text()
+                    .to_string()
+            }
+        };
+
+        assert_eq!(send("/hit", "en").await, "0 en");
+        assert_eq!(send("/hit", "en").await, "0 en");
+        assert_eq!(send("/hit", "da").await, "1 da");
+        assert_eq!(send("/hit", "en").await, "0 en");
+
+        assert_eq!(send("/no-store", "en").await, "2 en");
+        assert_eq!(send("/no-store", "en").await, "3 en");
+        assert_eq!(send("/private", "en").await, "4 en");
+        assert_eq!(send("/private", "en").await, "5 en");
+        assert_eq!(calls.load(Ordering::SeqCst), 6);
+    }
+}
@@ -0,0 +1,123 @@
+//! Answering panics with `500 Internal Server Error`.
+//!
+//! Wraps [`dale::CatchPanic`]. The panic message is logged, and the client
+//! gets the status line only.
+use crate::{Body, Error, Outcome, Reply};
+use dale::{IntoOutcome, Middleware, Service};
+use futures_core::ready;
+use http::{Request, Response, StatusCode};
+use pin_project_lite::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CatchPanic;
+
+impl<B, T> Middleware<Request<B>, T> for CatchPanic
+where
+    B: Body,
+    T: Service<Request<B>>,
+    <T::Output as IntoOutcome<Request<B>>>::Success: Reply<B>,
+    <T::Output as IntoOutcome<Request<B>>>::Failure: Into<Error>,
+{
+    type Service = CatchPanicService<T>;
+
+    fn wrap(&self, service: T) -> Self::Service {
+        CatchPanicService {
+            service: dale::CatchPanic.wrap(service),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CatchPanicService<T> {
+    service: dale::CatchPanicService<T>,
+}
+
+impl<B, T> Service<Request<B>> for CatchPanicService<T>
+where
+    B: Body,
+    T: Service<Request<B>>,
+    <T::Output as IntoOutcome<Request<B>>>::Success: Reply<B>,
+    <T::Output as IntoOutcome<Request<B>>>::Failure: Into<Error>,
+{
+    type Output = Outcome<B>;
+
+    type Future = CatchPanicFuture<T::Future, B>;
+
+    fn call(&self, req: Request<B>) -> Self::Future {
+        CatchPanicFuture {
+            future: self.service.call(req),
+        }
+    }
+}
+
+impl<T> dale::Describe for CatchPanicService<T>
+where
+    T: dale::Describe,
+{
+    fn describe(&self) -> dale::Description {
+        self.service.describe()
+    }
+}
+
+pin_project! {
+    pub struct CatchPanicFuture<F, B> {
+        #[pin]
+        future: dale::CatchPanicFuture<F, Request<B>>,
+    }
+}
+
+impl<F, B> Future for CatchPanicFuture<F, B>
+where
+    B: Body,
+    F: Future,
+    F::Output: IntoOutcome<Request<B>>,
+    <F::Output as IntoOutcome<Request<B>>>::Success: Reply<B>,
+    <F::Output as IntoOutcome<Request<B>>>::Failure: Into<Error>,
+{
+    type Output = Outcome<B>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let ret = match ready!(self.project().future.poll(cx)) {
+            dale::Outcome::Success(reply) => dale::Outcome::Success(reply.into_response()),
+            dale::Outcome::Failure(dale::Either::Left(err)) => dale::Outcome::Failure(err.into()),
+            dale::Outcome::Failure(dale::Either::Right(panic)) => {
+                tracing::error!("service {}", panic);
+                let status = StatusCode::INTERNAL_SERVER_ERROR;
+                let mut resp = Response::new(B::from_bytes(status.to_string().into_bytes()));
+                *resp.status_mut() = status;
+                dale::Outcome::Success(resp)
+            }
+            dale::Outcome::Next(req) => dale::Outcome::Next(req),
+        };
+
+        Poll::Ready(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{body::BoxBody, test::request};
+
+    #[tokio::test]
+    async fn test_catch_panic() {
+        let service = CatchPanic.wrap(|req: Request<BoxBody>| async move {
+            if req.uri().path() == "/panic" {
+                panic!("could not serialize");
+            }
+            Result::<_, Error>::Ok("ok")
+        });
+
+        let resp = request().path("/panic").send(&service).await;
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(resp.text(), "500 Internal Server Error");
+
+        let resp = request().send(&service).await;
+        assert_eq!(resp.text(), "ok");
+    }
+}
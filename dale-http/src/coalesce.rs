@@ -0,0 +1,260 @@
+//! Single-flight for HTTP services.
+//!
+//! Wraps [`dale::Coalesce`]. The leading response body is buffered so every
+//! waiter can be handed its own copy. By default only safe requests are
+//! coalesced, keyed by method and URI.
+use crate::{
+    common::SharedResponse,
+    error::{BoxError, Error},
+    Body, Outcome, Reply,
+};
+use dale::{boxed::BoxFuture, IntoOutcome, Middleware, RequestKey, Service};
+use http::{Method, Request, Uri};
+use std::{error::Error as StdError, fmt, sync::Arc};
+
+/// Key safe requests by method and URI.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MethodUri;
+
+impl<B> RequestKey<Request<B>> for MethodUri {
+    type Key = (Method, Uri);
+
+    fn key(&self, req: &Request<B>) -> Option<Self::Key> {
+        if req.method().is_safe() {
+            Some((req.method().clone(), req.uri().clone()))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Coalesce<F = MethodUri> {
+    key: F,
+}
+
+impl Coalesce {
+    pub fn new() -> Coalesce {
+        Coalesce { key: MethodUri }
+    }
+}
+
+impl Default for Coalesce {
+    fn default() -> Self {
+        Coalesce::new()
+    }
+}
+
+impl<F> Coalesce<F> {
+    pub fn with_key(key: F) -> Coalesce<F> {
+        Coalesce { key }
+    }
+}
+
+impl<B, T, F> Middleware<Request<B>, T> for Coalesce<F>
+where
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    T: Service<Request<B>> + Send + Sync + 'static,
+    T::Future: Send + 'static,
+    <T::Output as IntoOutcome<Request<B>>>::Success: Reply<B> + Send,
+    <T::Output as IntoOutcome<Request<B>>>::Failure: Into<Error> + Send,
+    F: RequestKey<Request<B>> + Clone,
+    F::Key: Send + Sync + 'static,
+{
+    type Service = CoalesceService<T, F, F::Key>;
+
+    fn wrap(&self, service: T) -> Self::Service {
+        CoalesceService {
+            service: dale::Coalesce::new(self.key.clone()).wrap(Buffered { service }),
+        }
+    }
+}
+
+pub struct CoalesceService<T, F, K> {
+    service: dale::CoalesceService<Buffered<T>, F, K, SharedResponse, SharedError>,
+}
+
+impl<T, F: Clone, K> Clone for CoalesceService<T, F, K> {
+    fn clone(&self) -> Self {
+        CoalesceService {
+            service: self.service.clone(),
+        }
+    }
+}
+
+impl<B, T, F, K> Service<Request<B>> for CoalesceService<T, F, K>
+where
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    T: Service<Request<B>> + Send + Sync + 'static,
+    T::Future: Send + 'static,
+    <T::Output as IntoOutcome<Request<B>>>::Success: Reply<B> + Send,
+    <T::Output as IntoOutcome<Request<B>>>::Failure: Into<Error> + Send,
+    F: RequestKey<Request<B>, Key = K>,
+    K: std::hash::Hash + Eq + Clone + Send + Sync + 'static,
+{
+    type Output = Outcome<B>;
+
+    type Future = BoxFuture<'static, Self::Output>;
+
+    fn call(&self, req: Request<B>) -> Self::Future {
+        let future = self.service.call(req);
+        Box::pin(async move {
+            match future.await {
+                dale::Outcome::Success(shared) => dale::Outcome::Success(shared.to_response()),
+                dale::Outcome::Failure(err) => dale::Outcome::Failure(err.into()),
+                dale::Outcome::Next(req) => dale::Outcome::Next(req),
+            }
+        })
+    }
+}
+
+impl<T, F, K> dale::Describe for CoalesceService<T, F, K>
+where
+    T: dale::Describe,
+{
+    fn describe(&self) -> dale::Description {
+        self.service.describe()
+    }
+}
+
+/// Buffers the inner response so it can be shared between waiters.
+pub struct Buffered<T> {
+    service: T,
+}
+
+impl<B, T> Service<Request<B>> for Buffered<T>
+where
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    T: Service<Request<B>>,
+    T::Future: Send + 'static,
+    <T::Output as IntoOutcome<Request<B>>>::Success: Reply<B> + Send,
+    <T::Output as IntoOutcome<Request<B>>>::Failure: Into<Error> + Send,
+{
+    type Output = dale::Outcome<SharedResponse, SharedError, Request<B>>;
+
+    type Future = BoxFuture<'static, Self::Output>;
+
+    fn call(&self, req: Request<B>) -> Self::Future {
+        let future = self.service.call(req);
+        Box::pin(async move {
+            let outcome = future.await.into_outcome();
+            let resp = match outcome {
+                dale::Outcome::Success(reply) => reply.into_response(),
+                dale::Outcome::Failure(err) => {
+                    return dale::Outcome::Failure(SharedError(Arc::new(err.into())))
+                }
+                dale::Outcome::Next(req) => return dale::Outcome::Next(req),
+            };
+
+            match SharedResponse::buffer(resp).await {
+                Ok(shared) => dale::Outcome::Success(shared),
+                Err(err) => dale::Outcome::Failure(SharedError(Arc::new(err))),
+            }
+        })
+    }
+}
+
+impl<T> dale::Describe for Buffered<T>
+where
+    T: dale::Describe,
+{
+    fn describe(&self) -> dale::Description {
+        self.service.describe()
+    }
+}
+
+/// The leading call's error, shared between waiters.
+#[derive(Debug, Clone)]
+pub struct SharedError(Arc<Error>);
+
+impl fmt::Display for SharedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.error.fmt(f)
+    }
+}
+
+impl StdError for SharedError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&*self.0)
+    }
+}
+
+/// Keeps the status and headers of the shared error, so waiters get the
+/// same response as the leading call.
+impl From<SharedError> for Error {
+    fn from(err: SharedError) -> Error {
+        let err = match Arc::try_unwrap(err.0) {
+            Ok(err) => return err,
+            Err(err) => SharedError(err),
+        };
+
+        let status = err.0.status();
+        let headers = err.0.headers().clone();
+        let mut error = Error::new(err).with_status(status);
+        *error.headers_mut() = headers;
+        error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{body::BoxBody, test::request};
+    use http::{header, Response, StatusCode};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_coalesce() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = Coalesce::new().wrap({
+            let calls = calls.clone();
+            move |req: Request<BoxBody>| {
+                let calls = calls.clone();
+                async move {
+                    let n = calls.fetch_add(1, Ordering::SeqCst);
+                    // Give the other requests a chance to join.
+                    for _ in 0..10 {
+                        tokio::task::yield_now().await;
+                    }
+
+                    match req.uri().path() {
+                        "/busy" => Err(Error::new("slow down")
+                            .with_status(StatusCode::TOO_MANY_REQUESTS)
+                            .with_header(header::RETRY_AFTER, "1".parse().unwrap())),
+                        _ => Ok(Response::new(BoxBody::from_bytes(
+                            n.to_string().into_bytes(),
+                        ))),
+                    }
+                }
+            }
+        });
+
+        let send = |method: Method, path: &'static str| {
+            let service = service.clone();
+            async move { request().method(method).path(path).send(&service).await }
+        };
+
+        let (a, b) = tokio::join!(send(Method::GET, "/"), send(Method::GET, "/"));
+        assert_eq!(a.text(), "0");
+        assert_eq!(b.text(), "0");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let (a, b) = tokio::join!(send(Method::POST, "/"), send(Method::POST, "/"));
+        assert_ne!(a.text(), b.text());
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let (a, b) = tokio::join!(send(Method::GET, "/busy"), send(Method::GET, "/busy"));
+        for resp in [a, b] {
+            assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+            assert_eq!(resp.header("retry-after"), Some("1"));
+            assert_eq!(resp.text(), "slow down");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+}
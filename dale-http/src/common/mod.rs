@@ -1,7 +1,8 @@
 mod aggregate;
 pub mod buf;
 // mod stream;
+mod shared;
 mod to_bytes;
 mod to_string;
 
-pub use self::{aggregate::*, to_bytes::*, to_string::*};
+pub use self::{aggregate::*, shared::*, to_bytes::*, to_string::*};
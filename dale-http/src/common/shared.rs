@@ -0,0 +1,55 @@
+use crate::{
+    common::ToBytes,
+    error::{BoxError, Error},
+    Body,
+};
+use bytes::Bytes;
+use http::{HeaderMap, Response, StatusCode, Version};
+use http_body::Body as HttpBody;
+
+/// A response with its body buffered, so it can be replayed any number of times.
+#[derive(Debug, Clone)]
+pub struct SharedResponse {
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl SharedResponse {
+    pub async fn buffer<B>(resp: Response<B>) -> Result<SharedResponse, Error>
+    where
+        B: HttpBody,
+        B::Error: Into<BoxError>,
+    {
+        let (parts, body) = resp.into_parts();
+        let body = ToBytes::new(body).await.map_err(Error::new)?;
+
+        Ok(SharedResponse {
+            status: parts.status,
+            version: parts.version,
+            headers: parts.headers,
+            body,
+        })
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    pub fn body(&self) -> &Bytes {
+        &self.body
+    }
+
+    pub fn to_response<B: Body>(&self) -> Response<B> {
+        let mut resp = Response::new(B::from_bytes(self.body.to_vec()));
+        *resp.status_mut() = self.status;
+        *resp.version_mut() = self.version;
+        *resp.headers_mut() = self.headers.clone();
+        resp
+    }
+}
@@ -0,0 +1,421 @@
+//! Rendering failures as responses.
+//!
+//! [`ErrorHandler`] answers every `Outcome::Failure` with a response.
+//! Handlers registered for an error type are tried first, in order. Other
+//! errors get the default rendering: `application/problem+json` (RFC 7807),
+//! or HTML or plain text when the `Accept` header prefers them. Server
+//! errors are logged, and their details are hidden unless exposed.
+use super::Error;
+use crate::{Body, Outcome, Reply};
+use dale::{IntoOutcome, Middleware, Service};
+use futures_core::ready;
+use http::{header, HeaderValue, Request, Response};
+use pin_project_lite::pin_project;
+use std::{
+    error::Error as StdError,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+type Handler<B> = Arc<dyn Fn(&Error) -> Option<Response<B>> + Send + Sync>;
+
+pub struct ErrorHandler<B> {
+    handlers: Vec<Handler<B>>,
+    expose_internal: bool,
+    log: bool,
+}
+
+impl<B: Body> ErrorHandler<B> {
+    pub fn new() -> ErrorHandler<B> {
+        ErrorHandler {
+            handlers: Vec::new(),
+            expose_internal: false,
+            log: true,
+        }
+    }
+
+    /// Answer errors wrapping an `E` with `handler`. Headers carried by the
+    /// error, like `Allow` or `WWW-Authenticate`, are added to the response
+    /// unless the handler set them itself.
+    pub fn handle<E, F, R>(mut self, handler: F) -> Self
+    where
+        E: StdError + 'static,
+        F: Fn(&E) -> R + Send + Sync + 'static,
+        R: Reply<B>,
+    {
+        self.handlers.push(Arc::new(move |err: &Error| {
+            err.downcast_ref::<E>()
+                .map(|err| handler(err).into_response())
+        }));
+        self
+    }
+
+    /// Show the message of server errors to clients too.
+    pub fn expose_internal(mut self, expose: bool) -> Self {
+        self.expose_internal = expose;
+        self
+    }
+
+    /// Log server errors. On by default.
+    pub fn log(mut self, log: bool) -> Self {
+        self.log = log;
+        self
+    }
+
+    fn render(&self, err: Error, accept: Option<&HeaderValue>) -> Response<B> {
+        if self.log && err.status().is_server_error() {
+            tracing::error!("{}", err);
+        }
+
+        if let Some(mut resp) = self.handlers.iter().find_map(|handler| handler(&err)) {
+            let headers = err.headers();
+            for name in headers.keys() {
+                if !resp.headers().contains_key(name) {
+                    for value in headers.get_all(name) {
+                        resp.headers_mut().append(name.clone(), value.clone());
+                    }
+                }
+            }
+            return resp;
+        }
+
+        render(err, accept, self.expose_internal)
+    }
+}
+
+impl<B: Body> Default for ErrorHandler<B> {
+    fn default() -> Self {
+        ErrorHandler::new()
+    }
+}
+
+impl<B> Clone for ErrorHandler<B> {
+    fn clone(&self) -> Self {
+        ErrorHandler {
+            handlers: self.handlers.clone(),
+            expose_internal: self.expose_internal,
+            log: self.log,
+        }
+    }
+}
+
+impl<B> fmt::Debug for ErrorHandler<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErrorHandler")
+            .field("handlers", &self.handlers.len())
+            .field("expose_internal", &self.expose_internal)
+            .field("log", &self.log)
+            .finish()
+    }
+}
+
+impl<B, T> Middleware<Request<B>, T> for ErrorHandler<B>
+where
+    B: Body,
+    T: Service<Request<B>>,
+    <T::Output as IntoOutcome<Request<B>>>::Success: Reply<B>,
+    <T::Output as IntoOutcome<Request<B>>>::Failure: Into<Error>,
+{
+    type Service = ErrorHandlerService<T, B>;
+
+    fn wrap(&self, service: T) -> Self::Service {
+        ErrorHandlerService {
+            service,
+            handler: self.clone(),
+        }
+    }
+}
+
+pub struct ErrorHandlerService<T, B> {
+    service: T,
+    handler: ErrorHandler<B>,
+}
+
+impl<T: Clone, B> Clone for ErrorHandlerService<T, B> {
+    fn clone(&self) -> Self {
+        ErrorHandlerService {
+            service: self.service.clone(),
+            handler: self.handler.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug, B> fmt::Debug for ErrorHandlerService<T, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ErrorHandlerService")
+            .field("service", &self.service)
+            .field("handler", &self.handler)
+            .finish()
+    }
+}
+
+impl<B, T> Service<Request<B>> for ErrorHandlerService<T, B>
+where
+    B: Body,
+    T: Service<Request<B>>,
+    <T::Output as IntoOutcome<Request<B>>>::Success: Reply<B>,
+    <T::Output as IntoOutcome<Request<B>>>::Failure: Into<Error>,
+{
+    type Output = Outcome<B>;
+
+    type Future = ErrorHandlerFuture<T::Future, B>;
+
+    fn call(&self, req: Request<B>) -> Self::Future {
+        ErrorHandlerFuture {
+            accept: req.headers().get(header::ACCEPT).cloned(),
+            future: self.service.call(req),
+            handler: self.handler.clone(),
+        }
+    }
+}
+
+impl<T, B> dale::Describe for ErrorHandlerService<T, B>
+where
+    T: dale::Describe,
+{
+    fn describe(&self) -> dale::Description {
+        dale::Description::new("error_handler").with_child(self.service.describe())
+    }
+}
+
+pin_project! {
+    pub struct ErrorHandlerFuture<F, B> {
+        #[pin]
+        future: F,
+        handler: ErrorHandler<B>,
+        accept: Option<HeaderValue>,
+    }
+}
+
+impl<F, B> Future for ErrorHandlerFuture<F, B>
+where
+    B: Body,
+    F: Future,
+    F::Output: IntoOutcome<Request<B>>,
+    <F::Output as IntoOutcome<Request<B>>>::Success: Reply<B>,
+    <F::Output as IntoOutcome<Request<B>>>::Failure: Into<Error>,
+{
+    type Output = Outcome<B>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let ret = match ready!(this.future.poll(cx)).into_outcome() {
+            dale::Outcome::Success(reply) => dale::Outcome::Success(reply.into_response()),
+            dale::Outcome::Failure(err) => {
+                dale::Outcome::Success(this.handler.render(err.into(), this.accept.as_ref()))
+            }
+            dale::Outcome::Next(req) => dale::Outcome::Next(req),
+        };
+
+        Poll::Ready(ret)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Problem,
+    Html,
+    Text,
+}
+
+/// The supported format with the highest quality in `accept`. Earlier
+/// entries win ties.
+fn negotiate(accept: Option<&HeaderValue>) -> Format {
+    let accept = match accept.and_then(|accept| accept.to_str().ok()) {
+        Some(accept) => accept,
+        None => return Format::Problem,
+    };
+
+    let mut best: Option<(Format, f32)> = None;
+    for mime in accept
+        .split(',')
+        .filter_map(|item| item.trim().parse::<mime::Mime>().ok())
+    {
+        let format = match (mime.type_(), mime.subtype().as_str()) {
+            (mime::TEXT, "html") => Format::Html,
+            (mime::TEXT, "plain") | (mime::TEXT, "*") => Format::Text,
+            (mime::APPLICATION, "problem+json")
+            | (mime::APPLICATION, "json")
+            | (mime::APPLICATION, "*")
+            | (mime::STAR, _) => Format::Problem,
+            _ => continue,
+        };
+
+        let q = mime
+            .get_param("q")
+            .and_then(|q| q.as_str().parse::<f32>().ok())
+            .unwrap_or(1.0);
+
+        if q > 0.0 && best.map(|(_, best)| q > best).unwrap_or(true) {
+            best = Some((format, q));
+        }
+    }
+
+    best.map(|(format, _)| format).unwrap_or(Format::Problem)
+}
+
+/// The default rendering of `err`, in the format preferred by `accept`.
+pub fn render<B: Body>(err: Error, accept: Option<&HeaderValue>, expose: bool) -> Response<B> {
+    let status = err.status();
+    let title = status.canonical_reason().unwrap_or("Unknown Error");
+    let detail = if expose || !status.is_server_error() {
+        Some(err.error.to_string())
+    } else {
+        None
+    };
+
+    let (content_type, body) = match negotiate(accept) {
+        Format::Problem => {
+            let mut body = format!(
+                r#"{{"type":"about:blank","title":{},"status":{}"#,
+                json_string(title),
+                status.as_u16()
+            );
+            if let Some(detail) = &detail {
+                body.push_str(&format!(r#","detail":{}"#, json_string(detail)));
+            }
+            body.push('}');
+            ("application/problem+json", body)
+        }
+        Format::Html => {
+            let heading = format!("{} {}", status.as_u16(), html_escape(title));
+            let detail = detail
+                .map(|detail| format!("<p>{}</p>", html_escape(&detail)))
+                .unwrap_or_default();
+            (
+                "text/html; charset=utf-8",
+                format!(
+                    "<!DOCTYPE html><html><head><title>{0}</title></head><body><h1>{0}</h1>{1}</body></html>",
+                    heading, detail
+                ),
+            )
+        }
+        Format::Text => (
+            "text/plain; charset=utf-8",
+            detail.unwrap_or_else(|| status.to_string()),
+        ),
+    };
+
+    let mut resp = Response::new(B::from_bytes(body.into_bytes()));
+    *resp.status_mut() = status;
+    resp.headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    resp.headers_mut().extend(err.headers);
+    resp
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{body::BoxBody, test::request, KnownError};
+    use http::StatusCode;
+
+    #[derive(Debug)]
+    struct Teapot;
+
+    impl fmt::Display for Teapot {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("short and stout")
+        }
+    }
+
+    impl StdError for Teapot {}
+
+    async fn fail(req: Request<BoxBody>) -> Result<Response<BoxBody>, Error> {
+        Err(match req.uri().path() {
+            "/large" => KnownError::PayloadTooLarge.into(),
+            "/teapot" => Error::new(KnownError::Internal(Box::new(Teapot)))
+                .with_header(header::RETRY_AFTER, HeaderValue::from_static("60"))
+                .with_header(header::CONTENT_TYPE, HeaderValue::from_static("x/ignored")),
+            _ => Error::new("secret database password"),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_error_handler() {
+        let service = ErrorHandler::new()
+            .handle(|_: &Teapot| {
+                let mut resp = Response::new(BoxBody::from_bytes(b"teapot".to_vec()));
+                *resp.status_mut() = StatusCode::IM_A_TEAPOT;
+                resp.headers_mut()
+                    .insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+                resp
+            })
+            .log(false)
+            .wrap(fail);
+
+        let resp = request().path("/large").send(&service).await;
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(
+            resp.header("content-type"),
+            Some("application/problem+json")
+        );
+        assert_eq!(
+            resp.text(),
+            r#"{"type":"about:blank","title":"Payload Too Large","status":413,"detail":"payload too large"}"#
+        );
+
+        let resp = request()
+            .header("accept", "application/json;q=0.5, text/html")
+            .send(&service)
+            .await;
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(resp
+            .header("content-type")
+            .unwrap()
+            .starts_with("text/html"));
+        assert!(!resp.text().contains("secret"));
+
+        let resp = request().path("/teapot").send(&service).await;
+        assert_eq!(resp.status(), StatusCode::IM_A_TEAPOT);
+        assert_eq!(resp.text(), "teapot");
+        assert_eq!(resp.header("retry-after"), Some("60"));
+        assert_eq!(resp.header("content-type"), Some("text/plain"));
+    }
+
+    #[cfg(feature = "hyper")]
+    #[tokio::test]
+    async fn test_error_handler_hyper() {
+        use hyper::service::Service as _;
+
+        async fn fail(_req: Request<hyper::Body>) -> Result<Response<hyper::Body>, Error> {
+            Err(KnownError::PayloadTooLarge.into())
+        }
+
+        let make = crate::hyper::make(ErrorHandler::new().log(false).wrap(fail));
+        let resp = make
+            .service(None)
+            .call(Request::new(hyper::Body::empty()))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -0,0 +1,279 @@
+use crate::{filters::BodyReadError, Body, Reply};
+use dale::Either;
+use http::{
+    header::{self, HeaderName},
+    HeaderMap, HeaderValue, Response, StatusCode,
+};
+use std::{convert::Infallible, error::Error as StdError, fmt};
+
+mod handler;
+
+pub use handler::*;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+pub type BoxError = Box<dyn StdError + Send + Sync>;
+
+/// An error with the HTTP status it should be answered with.
+///
+/// The status is the one set with [`Error::with_status`], or else derived
+/// from a wrapped [`KnownError`], or else `500 Internal Server Error`. As a
+/// [`Reply`], client errors show their message, while server errors only
+/// show the status, so internal details don't leak.
+#[derive(Debug)]
+pub struct Error {
+    pub(crate) error: BoxError,
+    status: Option<StatusCode>,
+    headers: HeaderMap,
+}
+
+impl Error {
+    pub fn new<E>(error: E) -> Error
+    where
+        E: Into<BoxError>,
+    {
+        Error {
+            error: error.into(),
+            status: None,
+            headers: HeaderMap::new(),
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+            .or_else(|| {
+                self.error
+                    .downcast_ref::<KnownError>()
+                    .map(KnownError::status)
+            })
+            .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    pub fn with_status(mut self, status: StatusCode) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Headers added to the response rendered from this error.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        &mut self.headers
+    }
+
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// The wrapped error as a `T`. Errors wrapped by a [`KnownError`] are
+    /// looked up too.
+    pub fn downcast_ref<T: StdError + 'static>(&self) -> Option<&T> {
+        if let Some(err) = self.error.downcast_ref::<T>() {
+            return Some(err);
+        }
+
+        match self.error.downcast_ref::<KnownError>()? {
+            KnownError::Internal(err) | KnownError::InvalidParam(_, err) => err.downcast_ref(),
+            #[cfg(feature = "serde")]
+            KnownError::Decode(err) => err.downcast_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn into_inner(self) -> BoxError {
+        self.error
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "http error: {}", self.error)
+    }
+}
+
+impl StdError for Error {
+    fn cause(&self) -> Option<&dyn StdError> {
+        Some(&*self.error)
+    }
+}
+
+impl<B: Body> Reply<B> for Error {
+    fn into_response(self) -> Response<B> {
+        let status = self.status();
+        let body = if status.is_server_error() {
+            tracing::error!("{}", self);
+            status.to_string()
+        } else {
+            self.error.to_string()
+        };
+
+        let mut resp = Response::new(B::from_bytes(body.into_bytes()));
+        *resp.status_mut() = status;
+        resp.headers_mut().insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("text/plain; charset=utf-8"),
+        );
+        resp.headers_mut().extend(self.headers);
+        resp
+    }
+}
+
+impl From<Infallible> for Error {
+    fn from(error: Infallible) -> Error {
+        Error::new(KnownError::Internal(Box::new(error)))
+    }
+}
+
+impl From<KnownError> for Error {
+    fn from(error: KnownError) -> Error {
+        Error::new(error)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Error {
+        Error::new(error)
+    }
+}
+
+#[cfg(feature = "hyper")]
+impl From<hyper::Error> for Error {
+    fn from(error: hyper::Error) -> Error {
+        Error::new(KnownError::Internal(Box::new(error)))
+    }
+}
+
+impl From<dale::Panic> for Error {
+    fn from(panic: dale::Panic) -> Error {
+        Error::new(KnownError::Internal(Box::new(panic)))
+    }
+}
+
+impl<E: StdError + Send + Sync + 'static> From<BodyReadError<E>> for Error {
+    fn from(error: BodyReadError<E>) -> Error {
+        Error::new(KnownError::Internal(Box::new(error)))
+    }
+}
+
+impl<L, R> From<Either<L, R>> for Error
+where
+    L: Into<Error>,
+    R: Into<Error>,
+{
+    fn from(err: Either<L, R>) -> Self {
+        match err {
+            Either::Left(left) => left.into(),
+            Either::Right(right) => right.into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum KnownError {
+    Internal(BoxError),
+    PayloadTooLarge,
+    UnsupportMediaType,
+    InvalidHeader(String),
+    MissingHeader(String),
+    InvalidParam(String, BoxError),
+    Utf8(std::str::Utf8Error),
+    #[cfg(feature = "serde")]
+    Decode(BoxError),
+}
+
+impl KnownError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            KnownError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            KnownError::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            KnownError::UnsupportMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            KnownError::InvalidHeader(_) | KnownError::MissingHeader(_) => StatusCode::BAD_REQUEST,
+            KnownError::InvalidParam(..) => StatusCode::BAD_REQUEST,
+            KnownError::Utf8(_) => StatusCode::BAD_REQUEST,
+            #[cfg(feature = "serde")]
+            KnownError::Decode(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl fmt::Display for KnownError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KnownError::Internal(err) => write!(f, "internal server error: {}", err),
+            KnownError::InvalidHeader(h) => write!(f, "invalid header: {}", h),
+            KnownError::MissingHeader(h) => write!(f, "missing header: {}", h),
+            KnownError::InvalidParam(name, err) => {
+                write!(f, "invalid path parameter {}: {}", name, err)
+            }
+            KnownError::PayloadTooLarge => write!(f, "payload too large"),
+            KnownError::UnsupportMediaType => write!(f, "unsupported media type"),
+            KnownError::Utf8(err) => write!(f, "encoding error: {}", err),
+            #[cfg(feature = "serde")]
+            KnownError::Decode(err) => write!(f, "decode error: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<crate::encoder::DecodeError> for KnownError {
+    fn from(value: crate::encoder::DecodeError) -> Self {
+        use crate::encoder::DecodeErrorKind;
+        match value.kind {
+            DecodeErrorKind::Syntax(err) => KnownError::Decode(err),
+            DecodeErrorKind::UnsupportedMediaType => KnownError::UnsupportMediaType,
+            DecodeErrorKind::Transport(err) => KnownError::Internal(err),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<crate::encoder::DecodeError> for Error {
+    fn from(value: crate::encoder::DecodeError) -> Self {
+        let err: KnownError = value.into();
+        Error::new(err)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<crate::encoder::EncodeError> for Error {
+    fn from(value: crate::encoder::EncodeError) -> Self {
+        Error::new(KnownError::Internal(value.into()))
+    }
+}
+
+impl StdError for KnownError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status() {
+        assert_eq!(
+            Error::from(KnownError::PayloadTooLarge).status(),
+            StatusCode::PAYLOAD_TOO_LARGE
+        );
+        assert_eq!(
+            Error::from(KnownError::MissingHeader("accept".into())).status(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            Error::new("boom").status(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+
+        let err = Error::from(KnownError::Internal(Box::new(fmt::Error)));
+        assert!(err.downcast_ref::<fmt::Error>().is_some());
+        assert!(err.downcast_ref::<std::io::Error>().is_none());
+
+        let resp: Response<crate::body::BoxBody> = Error::from(KnownError::UnsupportMediaType)
+            .with_status(StatusCode::NOT_ACCEPTABLE)
+            .with_header(header::VARY, HeaderValue::from_static("accept"))
+            .into_response();
+        assert_eq!(resp.status(), StatusCode::NOT_ACCEPTABLE);
+        assert_eq!(resp.headers()[header::VARY], "accept");
+    }
+}
@@ -0,0 +1,44 @@
+use dale::{filters::One, Outcome, Service};
+use http::Request;
+use std::net::{IpAddr, SocketAddr};
+
+use crate::{error::Error, forwarded};
+
+#[derive(Debug, Clone, Copy)]
+pub struct RemoteAddr;
+
+impl<B> Service<Request<B>> for RemoteAddr {
+    type Output = Outcome<(Request<B>, One<Option<SocketAddr>>), Error, Request<B>>;
+
+    type Future = std::future::Ready<Self::Output>;
+
+    fn call(&self, req: Request<B>) -> Self::Future {
+        let addr = forwarded::remote_addr(&req);
+        std::future::ready(Outcome::Success((req, (addr,))))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ClientIp;
+
+impl<B> Service<Request<B>> for ClientIp {
+    type Output = Outcome<(Request<B>, One<Option<IpAddr>>), Error, Request<B>>;
+
+    type Future = std::future::Ready<Self::Output>;
+
+    fn call(&self, req: Request<B>) -> Self::Future {
+        let ip = forwarded::client_ip(&req);
+        std::future::ready(Outcome::Success((req, (ip,))))
+    }
+}
+
+/// The address of the immediate peer.
+pub fn remote_addr() -> RemoteAddr {
+    RemoteAddr
+}
+
+/// The address of the client, looking through trusted proxies. See
+/// [`forwarded::client_ip`].
+pub fn client_ip() -> ClientIp {
+    ClientIp
+}
@@ -1,14 +1,15 @@
 use std::future::Future;
 
-use crate::error::Error;
-use dale::{filters::One, Outcome, Service};
+use crate::{error::Error, rejection::Rejections};
+use dale::{filters::One, Describe, Description, Outcome, Service};
 use http::{Method, Request};
 
 pub fn get<B: Send + 'static>() -> impl Service<
     Request<B>,
     Output = Outcome<(Request<B>, ()), Error, Request<B>>,
     Future = impl Future + Send,
-> + Copy {
+> + Describe
+       + Copy {
     method_is::<_, B>(|| &Method::GET)
 }
 
@@ -16,7 +17,8 @@ pub fn post<B: Send + 'static>() -> impl Service<
     Request<B>,
     Output = Outcome<(Request<B>, ()), Error, Request<B>>,
     Future = impl Future + Send,
-> + Copy {
+> + Describe
+       + Copy {
     method_is::<_, B>(|| &Method::POST)
 }
 
@@ -24,7 +26,8 @@ pub fn head<B: Send + 'static>() -> impl Service<
     Request<B>,
     Output = Outcome<(Request<B>, ()), Error, Request<B>>,
     Future = impl Future + Send,
-> + Copy {
+> + Describe
+       + Copy {
     method_is::<_, B>(|| &Method::HEAD)
 }
 
@@ -32,7 +35,8 @@ pub fn put<B: Send + 'static>() -> impl Service<
     Request<B>,
     Output = Outcome<(Request<B>, ()), Error, Request<B>>,
     Future = impl Future + Send,
-> + Copy {
+> + Describe
+       + Copy {
     method_is::<_, B>(|| &Method::PUT)
 }
 
@@ -40,7 +44,8 @@ pub fn patch<B: Send + 'static>() -> impl Service<
     Request<B>,
     Output = Outcome<(Request<B>, ()), Error, Request<B>>,
     Future = impl Future + Send,
-> + Copy {
+> + Describe
+       + Copy {
     method_is::<_, B>(|| &Method::PATCH)
 }
 
@@ -48,7 +53,8 @@ pub fn delete<B: Send + 'static>() -> impl Service<
     Request<B>,
     Output = Outcome<(Request<B>, ()), Error, Request<B>>,
     Future = impl Future + Send,
-> + Copy {
+> + Describe
+       + Copy {
     method_is::<_, B>(|| &Method::DELETE)
 }
 
@@ -56,7 +62,8 @@ pub fn options<B: Send + 'static>() -> impl Service<
     Request<B>,
     Output = Outcome<(Request<B>, ()), Error, Request<B>>,
     Future = impl Future + Send,
-> + Copy {
+> + Describe
+       + Copy {
     method_is::<_, B>(|| &Method::OPTIONS)
 }
 pub fn method<B: Send + 'static>() -> impl Service<
@@ -76,17 +83,41 @@ pub fn method_is<F, B: Send + 'static>(
     Request<B>,
     Output = Outcome<(Request<B>, ()), Error, Request<B>>,
     Future = impl Future + Send,
-> + Copy
+> + Describe
+       + Copy
 where
     F: 'static + Send + Fn() -> &'static Method + Copy,
 {
-    move |req: Request<B>| async move {
-        let method = func();
+    MethodIs(func)
+}
+
+#[derive(Clone, Copy)]
+struct MethodIs<F>(F);
+
+impl<F, B> Service<Request<B>> for MethodIs<F>
+where
+    F: Fn() -> &'static Method,
+{
+    type Output = Outcome<(Request<B>, ()), Error, Request<B>>;
+    type Future = std::future::Ready<Self::Output>;
+
+    fn call(&self, mut req: Request<B>) -> Self::Future {
+        let method = (self.0)();
         tracing::trace!("method::{:?}?: {:?}", method, req.method());
         if req.method() == method {
-            Outcome::Success((req, ()))
+            std::future::ready(Outcome::Success((req, ())))
         } else {
-            Outcome::Next(req)
+            Rejections::record_method(&mut req, method.clone());
+            std::future::ready(Outcome::Next(req))
         }
     }
 }
+
+impl<F> Describe for MethodIs<F>
+where
+    F: Fn() -> &'static Method,
+{
+    fn describe(&self) -> Description {
+        Description::new("method").with_detail((self.0)())
+    }
+}
@@ -1,14 +1,21 @@
 mod body;
+mod client;
 pub mod header;
 mod method;
 mod misc;
 mod mount;
 mod url;
 
+#[cfg(feature = "router")]
+mod params;
+
 #[cfg(feature = "serde")]
 mod encode;
 
-pub use self::{body::*, method::*, misc::*, mount::*, url::*};
+pub use self::{body::*, client::*, method::*, misc::*, mount::*, url::*};
 
 #[cfg(feature = "serde")]
 pub use encode::*;
+
+#[cfg(feature = "router")]
+pub use params::*;
@@ -1,9 +1,7 @@
-use std::str::FromStr;
-
 use dale::{filters::One, Outcome, Service};
 use http::{Request, Uri};
 
-use crate::{error::Error, mount::MountPath};
+use crate::{error::Error, forwarded, mount::MountPath};
 
 #[derive(Debug, Clone, Copy)]
 pub struct RealPath;
@@ -22,6 +20,9 @@ impl<B> Service<Request<B>> for RealPath {
     }
 }
 
+/// The request URI before mounting. Includes the scheme and host reported by
+/// a trusted proxy, see [`forwarded`](crate::forwarded), or else the host
+/// from the `Host` header. Fails with `400 Bad Request` when they don't parse.
 #[derive(Debug, Clone, Copy)]
 pub struct RealUri;
 
@@ -31,29 +32,28 @@ impl<B> Service<Request<B>> for RealUri {
     type Future = std::future::Ready<Self::Output>;
 
     fn call(&self, req: Request<B>) -> Self::Future {
-        let url = req.uri();
-        let p = match req.extensions().get::<MountPath>() {
+        let path = match req.extensions().get::<MountPath>() {
             Some(p) => p.real_path(&req),
             None => req.uri().path().to_owned(),
         };
-        let port = url.port();
-        let mut o = Vec::default();
-        if let Some(s) = url.scheme_str() {
-            o.push(s);
-        }
-        if let Some(s) = url.authority() {
-            o.push(s.as_str());
-        }
-        if let Some(p) = &port {
-            o.extend([":", p.as_str()]);
-        }
 
-        o.push(&p);
-        if let Some(s) = url.query() {
-            o.extend(["?", s]);
+        let path_and_query = match req.uri().query() {
+            Some(query) => format!("{}?{}", path, query),
+            None => path,
+        };
+
+        let mut uri = Uri::builder();
+        match forwarded::origin(&req) {
+            Ok((scheme, Some(authority))) => uri = uri.scheme(scheme).authority(authority),
+            Ok((_, None)) => {}
+            Err(err) => return std::future::ready(Outcome::Failure(err.into())),
         }
 
-        let uri = Uri::from_str(&o.join("")).unwrap();
+        let uri = match uri.path_and_query(path_and_query).build() {
+            Ok(uri) => uri,
+            Err(err) => return std::future::ready(Outcome::Failure(Error::new(err))),
+        };
+
         std::future::ready(Outcome::Success((req, (uri,))))
     }
 }
@@ -65,3 +65,30 @@ pub fn realuri() -> RealUri {
 pub fn realpath() -> RealPath {
     RealPath
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{body::BoxBody, forwarded::TrustedProxies, test::request};
+    use dale_test::{assert_failure, assert_success};
+    use std::net::SocketAddr;
+
+    #[test]
+    fn test_realuri() {
+        let req = |header: &'static str, value: &'static str| {
+            request()
+                .path("/x?page=2")
+                .header("host", "example.com")
+                .header(header, value)
+                .extension(SocketAddr::from(([10, 0, 0, 1], 4000)))
+                .extension(TrustedProxies::new(["10.0.0.0/8"]).unwrap())
+                .build::<BoxBody>()
+        };
+
+        let (_, (uri,)) = assert_success!(realuri(), req("x-forwarded-proto", "https"));
+        assert_eq!(uri, "https://example.com/x?page=2");
+
+        let err = assert_failure!(realuri(), req("x-forwarded-host", "exa mple.com"));
+        assert_eq!(err.status(), http::StatusCode::BAD_REQUEST);
+    }
+}
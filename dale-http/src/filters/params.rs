@@ -0,0 +1,196 @@
+use dale::{filters::One, Outcome, Service};
+use http::Request;
+use std::{borrow::Cow, marker::PhantomData, str::FromStr};
+
+use crate::{
+    error::{BoxError, Error, KnownError},
+    RequestExt,
+};
+
+pub struct Param<T> {
+    name: Cow<'static, str>,
+    _t: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Param<T> {
+    fn clone(&self) -> Self {
+        Param {
+            name: self.name.clone(),
+            _t: PhantomData,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Param<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Param").field("name", &self.name).finish()
+    }
+}
+
+impl<B, T> Service<Request<B>> for Param<T>
+where
+    T: FromStr,
+    T::Err: Into<BoxError>,
+{
+    type Output = Outcome<(Request<B>, One<T>), Error, Request<B>>;
+
+    type Future = std::future::Ready<Self::Output>;
+
+    fn call(&self, req: Request<B>) -> Self::Future {
+        let ret = match req.params().get(&self.name).map(str::parse::<T>) {
+            Some(Ok(value)) => Outcome::Success((req, (value,))),
+            Some(Err(err)) => {
+                Outcome::Failure(KnownError::InvalidParam(self.name.to_string(), err.into()).into())
+            }
+            None => Outcome::Next(req),
+        };
+        std::future::ready(ret)
+    }
+}
+
+/// The route parameter `name`, parsed as a `T`. Passes the request on when
+/// the route has no such parameter, and fails with `400 Bad Request` when it
+/// doesn't parse.
+pub fn param<T>(name: impl Into<Cow<'static, str>>) -> Param<T>
+where
+    T: FromStr,
+{
+    Param {
+        name: name.into(),
+        _t: PhantomData,
+    }
+}
+
+#[cfg(feature = "serde")]
+pub struct DecodeParams<T> {
+    _t: PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> Clone for DecodeParams<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> Copy for DecodeParams<T> {}
+
+#[cfg(feature = "serde")]
+impl<T> std::fmt::Debug for DecodeParams<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DecodeParams")
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<B, T> Service<Request<B>> for DecodeParams<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    type Output = Outcome<(Request<B>, One<T>), Error, Request<B>>;
+
+    type Future = std::future::Ready<Self::Output>;
+
+    fn call(&self, req: Request<B>) -> Self::Future {
+        // Going through the urlencoded format gets numbers and booleans
+        // parsed from their string values.
+        let ret = serde_urlencoded::to_string(req.params().iter().collect::<Vec<_>>())
+            .map_err(BoxError::from)
+            .and_then(|encoded| serde_urlencoded::from_str::<T>(&encoded).map_err(BoxError::from));
+
+        std::future::ready(match ret {
+            Ok(value) => Outcome::Success((req, (value,))),
+            Err(err) => Outcome::Failure(KnownError::Decode(err).into()),
+        })
+    }
+}
+
+/// All route parameters, deserialized into a `T`. Fails with
+/// `400 Bad Request` when they don't fit.
+#[cfg(feature = "serde")]
+pub fn params<T>() -> DecodeParams<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    DecodeParams { _t: PhantomData }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        body::BoxBody,
+        router::{Params, Router, Routing},
+        test::request,
+    };
+    use dale::IntoService;
+    use dale_test::{assert_failure, assert_next, assert_success};
+    use router::Params as _;
+
+    #[test]
+    fn test_param() {
+        let req = |id: &'static str| {
+            let mut params = Params::default();
+            params.set("id".into(), id.into());
+            request().extension(params).build::<BoxBody>()
+        };
+
+        let (_, (id,)) = assert_success!(param::<u32>("id"), req("42"));
+        assert_eq!(id, 42);
+
+        let err = assert_failure!(param::<u32>("id"), req("forty-two"));
+        assert_eq!(err.status(), http::StatusCode::BAD_REQUEST);
+
+        assert_next!(param::<u32>("name"), req("42"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_decode_params() {
+        #[derive(Debug, serde::Deserialize)]
+        struct Post {
+            id: u32,
+            slug: String,
+        }
+
+        let req = |id: &'static str| {
+            let mut params = Params::default();
+            params.set("id".into(), id.into());
+            params.set("slug".into(), "hello world".into());
+            request().extension(params).build::<BoxBody>()
+        };
+
+        let (_, (post,)) = assert_success!(params::<Post>(), req("42"));
+        assert_eq!(post.id, 42);
+        assert_eq!(post.slug, "hello world");
+
+        let err = assert_failure!(params::<Post>(), req("forty-two"));
+        assert_eq!(err.status(), http::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_router_params() {
+        let mut router = Router::<BoxBody>::new();
+        router
+            .get("/users/:name/*rest", |req: Request<BoxBody>| async move {
+                let params = req.params();
+                format!(
+                    "{:?} {:?}",
+                    params.iter().collect::<Vec<_>>(),
+                    params.wildcard()
+                )
+            })
+            .unwrap();
+        let service = router.into_service().unwrap();
+
+        let resp = request()
+            .path("/users/j%C3%B8rgen/a/b%20c")
+            .send(&service)
+            .await;
+        assert_eq!(
+            resp.text(),
+            r#"[("name", "jørgen"), ("rest", "a/b c")] Some("a/b c")"#
+        );
+    }
+}
@@ -1,8 +1,11 @@
-use dale::{filters::One, Outcome, Service};
+use dale::{filters::One, Describe, Description, Outcome, Service};
 use futures_core::Future;
 use http::{Request, Uri};
 
-use crate::error::Error;
+use crate::{
+    error::Error,
+    rejection::{Rejection, Rejections},
+};
 
 pub fn url<B: Send + 'static>() -> impl Service<
     Request<B>,
@@ -21,16 +24,31 @@ pub fn path<P: ToString, B: Send + 'static>(
     Request<B>,
     Output = Outcome<(Request<B>, ()), Error, Request<B>>,
     Future = impl Future + Send,
-> + Clone {
-    let p = path.to_string();
-    move |req: Request<B>| {
-        let eql = req.uri().path() == p;
-        async move {
-            if eql {
-                Outcome::Success((req, ()))
-            } else {
-                Outcome::Next(req)
-            }
+> + Describe
+       + Clone {
+    ExactPath(path.to_string())
+}
+
+#[derive(Clone)]
+struct ExactPath(String);
+
+impl<B> Service<Request<B>> for ExactPath {
+    type Output = Outcome<(Request<B>, ()), Error, Request<B>>;
+    type Future = std::future::Ready<Self::Output>;
+
+    fn call(&self, mut req: Request<B>) -> Self::Future {
+        if req.uri().path() == self.0 {
+            Rejections::path_matched(&mut req);
+            std::future::ready(Outcome::Success((req, ())))
+        } else {
+            Rejections::record(&mut req, Rejection::not_found());
+            std::future::ready(Outcome::Next(req))
         }
     }
 }
+
+impl Describe for ExactPath {
+    fn describe(&self) -> Description {
+        Description::new("path").with_detail(&self.0)
+    }
+}
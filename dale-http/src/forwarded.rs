@@ -0,0 +1,513 @@
+//! Client information reported by reverse proxies.
+//!
+//! `Forwarded`, `X-Forwarded-For`, `X-Forwarded-Proto`, `X-Forwarded-Host`
+//! and `X-Real-IP` are only believed when the immediate peer is one of the
+//! [`TrustedProxies`] installed on the request. Without them, or when the
+//! peer address isn't known, the headers are ignored.
+//!
+//! Only the headers of the [`ProxyHeader`] the proxies are configured with
+//! are read. The others may come straight from the client.
+use crate::error::KnownError;
+use dale::{Middleware, Service};
+use http::{
+    header,
+    uri::{Authority, Scheme},
+    HeaderMap, Request,
+};
+use std::{
+    error::Error as StdError,
+    fmt,
+    net::{IpAddr, SocketAddr},
+    str::FromStr,
+    sync::Arc,
+};
+
+/// A range of IP addresses, like `10.0.0.0/8` or `::1/128`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    pub fn new(addr: IpAddr, prefix: u8) -> Result<Cidr, InvalidCidr> {
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        if prefix > max {
+            return Err(InvalidCidr);
+        }
+        Ok(Cidr { addr, prefix })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            (IpAddr::V6(_), IpAddr::V4(ip)) => self.contains(IpAddr::V6(ip.to_ipv6_mapped())),
+            (IpAddr::V4(_), IpAddr::V6(ip)) => match ip.to_ipv4_mapped() {
+                Some(ip) => self.contains(IpAddr::V4(ip)),
+                None => false,
+            },
+        }
+    }
+}
+
+/// Parses `addr/prefix`. A bare address matches only itself.
+impl FromStr for Cidr {
+    type Err = InvalidCidr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('/') {
+            Some((addr, prefix)) => Cidr::new(
+                addr.trim().parse().map_err(|_| InvalidCidr)?,
+                prefix.trim().parse().map_err(|_| InvalidCidr)?,
+            ),
+            None => {
+                let addr: IpAddr = s.trim().parse().map_err(|_| InvalidCidr)?;
+                Cidr::new(addr, if addr.is_ipv4() { 32 } else { 128 })
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidCidr;
+
+impl fmt::Display for InvalidCidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid cidr")
+    }
+}
+
+impl StdError for InvalidCidr {}
+
+/// The headers the trusted proxies report the client with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProxyHeader {
+    /// `Forwarded` (RFC 7239), with its `for`, `proto` and `host` parameters.
+    Forwarded,
+    /// `X-Forwarded-For`, with `X-Forwarded-Proto` and `X-Forwarded-Host`.
+    #[default]
+    XForwardedFor,
+    /// `X-Real-IP`, with `X-Forwarded-Proto` and `X-Forwarded-Host`. The
+    /// proxy is expected to overwrite these rather than append to them, so
+    /// the last value of each is used.
+    XRealIp,
+}
+
+/// Proxies whose forwarding headers are believed.
+///
+/// Also a middleware, installing itself on every request passing through.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    cidrs: Arc<Vec<Cidr>>,
+    header: ProxyHeader,
+}
+
+impl TrustedProxies {
+    pub fn new<I, S>(cidrs: I) -> Result<TrustedProxies, InvalidCidr>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let cidrs = cidrs
+            .into_iter()
+            .map(|cidr| cidr.as_ref().parse())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(TrustedProxies {
+            cidrs: Arc::new(cidrs),
+            header: ProxyHeader::default(),
+        })
+    }
+
+    /// The headers to read. `X-Forwarded-For` by default.
+    pub fn header(mut self, header: ProxyHeader) -> Self {
+        self.header = header;
+        self
+    }
+
+    pub fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.cidrs.iter().any(|cidr| cidr.contains(ip))
+    }
+}
+
+impl<B, T> Middleware<Request<B>, T> for TrustedProxies
+where
+    T: Service<Request<B>>,
+{
+    type Service = TrustedProxiesService<T>;
+
+    fn wrap(&self, service: T) -> Self::Service {
+        TrustedProxiesService {
+            service,
+            proxies: self.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TrustedProxiesService<T> {
+    service: T,
+    proxies: TrustedProxies,
+}
+
+impl<B, T> Service<Request<B>> for TrustedProxiesService<T>
+where
+    T: Service<Request<B>>,
+{
+    type Output = T::Output;
+
+    type Future = T::Future;
+
+    fn call(&self, mut req: Request<B>) -> Self::Future {
+        req.extensions_mut().insert(self.proxies.clone());
+        self.service.call(req)
+    }
+}
+
+impl<T> dale::Describe for TrustedProxiesService<T>
+where
+    T: dale::Describe,
+{
+    fn describe(&self) -> dale::Description {
+        dale::Description::new("trusted_proxies").with_child(self.service.describe())
+    }
+}
+
+/// The address of the immediate peer, as inserted by the server.
+pub fn remote_addr<B>(req: &Request<B>) -> Option<SocketAddr> {
+    req.extensions().get::<SocketAddr>().copied()
+}
+
+/// The proxies to believe, if the peer is one of them.
+fn trusted<B>(req: &Request<B>) -> Option<&TrustedProxies> {
+    let peer = remote_addr(req)?.ip();
+    req.extensions()
+        .get::<TrustedProxies>()
+        .filter(|proxies| proxies.is_trusted(peer))
+}
+
+/// The address of the client. Forwarded hops are walked from the nearest
+/// one, and the first address that isn't a trusted proxy wins.
+pub fn client_ip<B>(req: &Request<B>) -> Option<IpAddr> {
+    let peer = remote_addr(req)?.ip();
+    let proxies = match trusted(req) {
+        Some(proxies) => proxies,
+        None => return Some(peer),
+    };
+
+    let chain = match proxies.header {
+        ProxyHeader::Forwarded => forwarded_for(req.headers()),
+        ProxyHeader::XForwardedFor => header_values(req.headers(), "x-forwarded-for")
+            .filter_map(parse_node)
+            .collect(),
+        ProxyHeader::XRealIp => header_values(req.headers(), "x-real-ip")
+            .last()
+            .and_then(parse_node)
+            .into_iter()
+            .collect(),
+    };
+
+    chain
+        .iter()
+        .rev()
+        .find(|ip| !proxies.is_trusted(**ip))
+        .or_else(|| chain.first())
+        .copied()
+        .or(Some(peer))
+}
+
+/// The scheme the client used, as reported by the outermost trusted proxy.
+pub fn forwarded_proto<B>(req: &Request<B>) -> Option<String> {
+    let proxies = trusted(req)?;
+    reported(req.headers(), proxies, "proto", "x-forwarded-proto")
+        .map(|proto| proto.to_ascii_lowercase())
+}
+
+/// The host the client asked for, as reported by the outermost trusted proxy.
+pub fn forwarded_host<B>(req: &Request<B>) -> Option<String> {
+    let proxies = trusted(req)?;
+    reported(req.headers(), proxies, "host", "x-forwarded-host")
+}
+
+/// The scheme and authority the client used: what a trusted proxy reported,
+/// or else what the request URI or `Host` header say. Fails with
+/// `400 Bad Request` when one of them doesn't parse.
+pub(crate) fn origin<B>(req: &Request<B>) -> Result<(Scheme, Option<Authority>), KnownError> {
+    let invalid = |name: &str| match trusted(req).map(|proxies| proxies.header) {
+        Some(ProxyHeader::Forwarded) => KnownError::InvalidHeader("forwarded".to_string()),
+        _ => KnownError::InvalidHeader(name.to_string()),
+    };
+
+    let scheme = match forwarded_proto(req) {
+        Some(proto) => proto
+            .parse::<Scheme>()
+            .map_err(|_| invalid("x-forwarded-proto"))?,
+        None => req.uri().scheme().cloned().unwrap_or(Scheme::HTTP),
+    };
+
+    let authority = match forwarded_host(req) {
+        Some(host) => Some(
+            host.parse::<Authority>()
+                .map_err(|_| invalid("x-forwarded-host"))?,
+        ),
+        None => match (req.uri().authority(), req.headers().get(header::HOST)) {
+            (Some(authority), _) => Some(authority.clone()),
+            (None, Some(host)) => Some(
+                Authority::try_from(host.as_bytes())
+                    .map_err(|_| KnownError::InvalidHeader("host".to_string()))?,
+            ),
+            (None, None) => None,
+        },
+    };
+
+    Ok((scheme, authority))
+}
+
+/// The `Forwarded` parameter `param`, or the `X-Forwarded-*` header `name`,
+/// depending on what the proxies send.
+fn reported(
+    headers: &HeaderMap,
+    proxies: &TrustedProxies,
+    param: &str,
+    name: &str,
+) -> Option<String> {
+    match proxies.header {
+        ProxyHeader::Forwarded => forwarded_param(headers, proxies, param),
+        ProxyHeader::XForwardedFor => x_forwarded(headers, proxies, name),
+        ProxyHeader::XRealIp => header_values(headers, name).last(),
+    }
+}
+
+fn is_trusted_node(proxies: &TrustedProxies, node: &str) -> bool {
+    matches!(parse_node(node.to_string()), Some(ip) if proxies.is_trusted(ip))
+}
+
+/// Picks the value added by the outermost trusted proxy: each trusted hop at
+/// the end of the chain vouches for the value before it. Earlier values come
+/// from the client and can't be believed.
+fn outermost<T>(values: &[T], hops: usize) -> Option<&T> {
+    values.iter().rev().nth(hops).or_else(|| values.first())
+}
+/// Comma separated values across every header named `name`.
+fn header_values<'a>(headers: &'a HeaderMap, name: &str) -> impl Iterator<Item = String> + 'a {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+/// `name=value` pairs of each `Forwarded` element, in order.
+fn forwarded_elements(headers: &HeaderMap) -> Vec<Vec<(String, String)>> {
+    header_values(headers, header::FORWARDED.as_str())
+        .map(|element| {
+            element
+                .split(';')
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(name, value)| {
+                    (
+                        name.trim().to_ascii_lowercase(),
+                        value.trim().trim_matches('"').to_string(),
+                    )
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn forwarded_param(headers: &HeaderMap, proxies: &TrustedProxies, name: &str) -> Option<String> {
+    let elements = forwarded_elements(headers);
+    let hops = elements
+        .iter()
+        .rev()
+        .take_while(|element| {
+            element
+                .iter()
+                .any(|(key, value)| key == "for" && is_trusted_node(proxies, value))
+        })
+        .count();
+
+    outermost(&elements, hops)?
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.clone())
+}
+
+fn x_forwarded(headers: &HeaderMap, proxies: &TrustedProxies, name: &str) -> Option<String> {
+    let chain = header_values(headers, "x-forwarded-for").collect::<Vec<_>>();
+    let hops = chain
+        .iter()
+        .rev()
+        .take_while(|node| is_trusted_node(proxies, node))
+        .count();
+
+    let values = header_values(headers, name).collect::<Vec<_>>();
+    outermost(&values, hops).cloned()
+}
+
+/// Addresses of the `Forwarded` chain, client first. Obfuscated and unknown
+/// nodes are skipped.
+fn forwarded_for(headers: &HeaderMap) -> Vec<IpAddr> {
+    forwarded_elements(headers)
+        .into_iter()
+        .flat_map(|element| element.into_iter())
+        .filter(|(key, _)| key == "for")
+        .filter_map(|(_, value)| parse_node(value))
+        .collect()
+}
+
+fn parse_node(node: String) -> Option<IpAddr> {
+    if let Some(rest) = node.strip_prefix('[') {
+        return rest.split(']').next()?.parse().ok();
+    }
+
+    node.parse::<IpAddr>()
+        .ok()
+        .or_else(|| node.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(peer: &str, header: ProxyHeader, headers: &[(&'static str, &str)]) -> Request<()> {
+        let mut req = Request::new(());
+        for (name, value) in headers {
+            req.headers_mut()
+                .append(*name, http::HeaderValue::from_str(value).unwrap());
+        }
+        req.extensions_mut()
+            .insert(SocketAddr::new(peer.parse().unwrap(), 4000));
+        req.extensions_mut().insert(
+            TrustedProxies::new(["10.0.0.0/8", "::1"])
+                .unwrap()
+                .header(header),
+        );
+        req
+    }
+
+    #[test]
+    fn test_cidr() {
+        let cidr: Cidr = "192.168.0.0/16".parse().unwrap();
+        assert!(cidr.contains("192.168.4.2".parse().unwrap()));
+        assert!(cidr.contains("::ffff:192.168.4.2".parse().unwrap()));
+        assert!(!cidr.contains("192.169.0.1".parse().unwrap()));
+        assert!("0.0.0.0/0"
+            .parse::<Cidr>()
+            .unwrap()
+            .contains("8.8.8.8".parse().unwrap()));
+        assert_eq!("10.0.0.0/33".parse::<Cidr>(), Err(InvalidCidr));
+    }
+
+    #[test]
+    fn test_client_ip() {
+        let xff = [("x-forwarded-for", "203.0.113.9, 198.51.100.1, 10.0.0.2")];
+
+        let req = request("10.0.0.1", ProxyHeader::XForwardedFor, &xff);
+        assert_eq!(client_ip(&req), Some("198.51.100.1".parse().unwrap()));
+
+        let req = request("192.0.2.1", ProxyHeader::XForwardedFor, &xff);
+        assert_eq!(client_ip(&req), Some("192.0.2.1".parse().unwrap()));
+
+        let req = request(
+            "::1",
+            ProxyHeader::Forwarded,
+            &[(
+                "forwarded",
+                r#"for="[2001:db8::17]:4711";proto=HTTPS;host=example.com"#,
+            )],
+        );
+        assert_eq!(client_ip(&req), Some("2001:db8::17".parse().unwrap()));
+        assert_eq!(forwarded_proto(&req).as_deref(), Some("https"));
+        assert_eq!(forwarded_host(&req).as_deref(), Some("example.com"));
+
+        let req = request(
+            "10.0.0.1",
+            ProxyHeader::XRealIp,
+            &[("x-real-ip", "203.0.113.9")],
+        );
+        assert_eq!(client_ip(&req), Some("203.0.113.9".parse().unwrap()));
+
+        let req = request(
+            "192.0.2.1",
+            ProxyHeader::XForwardedFor,
+            &[("x-forwarded-proto", "https")],
+        );
+        assert_eq!(forwarded_proto(&req), None);
+    }
+
+    #[test]
+    fn test_forwarded_spoofed() {
+        let req = request(
+            "10.0.0.1",
+            ProxyHeader::Forwarded,
+            &[
+                ("forwarded", "for=203.0.113.9;host=evil.example"),
+                ("forwarded", "for=198.51.100.1;proto=https;host=example.com"),
+            ],
+        );
+        assert_eq!(forwarded_proto(&req).as_deref(), Some("https"));
+        assert_eq!(forwarded_host(&req).as_deref(), Some("example.com"));
+
+        let req = request(
+            "10.0.0.1",
+            ProxyHeader::XForwardedFor,
+            &[
+                ("x-forwarded-for", "203.0.113.9, 198.51.100.1, 10.0.0.2"),
+                ("x-forwarded-host", "evil.example, example.com, internal"),
+            ],
+        );
+        assert_eq!(forwarded_host(&req).as_deref(), Some("example.com"));
+
+        let req = request(
+            "10.0.0.1",
+            ProxyHeader::XForwardedFor,
+            &[
+                ("x-forwarded-for", "198.51.100.1"),
+                ("x-forwarded-proto", "https"),
+            ],
+        );
+        assert_eq!(forwarded_proto(&req).as_deref(), Some("https"));
+    }
+
+    #[test]
+    fn test_other_headers_ignored() {
+        // The proxy only appends X-Forwarded-For, so the rest is the client's.
+        let req = request(
+            "10.0.0.1",
+            ProxyHeader::XForwardedFor,
+            &[
+                ("forwarded", "for=8.8.8.8;proto=https;host=evil.example"),
+                ("x-real-ip", "8.8.4.4"),
+                ("x-forwarded-for", "198.51.100.1"),
+            ],
+        );
+        assert_eq!(client_ip(&req), Some("198.51.100.1".parse().unwrap()));
+        assert_eq!(forwarded_proto(&req), None);
+        assert_eq!(forwarded_host(&req), None);
+
+        let req = request(
+            "10.0.0.1",
+            ProxyHeader::XForwardedFor,
+            &[("forwarded", "for=8.8.8.8"), ("x-real-ip", "8.8.4.4")],
+        );
+        assert_eq!(client_ip(&req), Some("10.0.0.1".parse().unwrap()));
+
+        let req = request(
+            "10.0.0.1",
+            ProxyHeader::Forwarded,
+            &[("x-forwarded-for", "8.8.8.8"), ("x-forwarded-host", "evil")],
+        );
+        assert_eq!(client_ip(&req), Some("10.0.0.1".parse().unwrap()));
+        assert_eq!(forwarded_host(&req), None);
+    }
+}
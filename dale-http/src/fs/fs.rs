@@ -1,8 +1,7 @@
 use super::file_conditional::file_conditional;
 use super::file_options::file_options;
 use crate::error::Error;
-use crate::filters;
-use crate::modifier::{Set, With};
+use crate::{filters, forwarded};
 use crate::{modifier::Modifier, Body};
 use dale::filters::One;
 use dale::{Outcome, Service, ServiceExt};
@@ -10,8 +9,10 @@ use dale_fs::{FileTypeMask, Node};
 use dale_runtime::fs::FS;
 use dale_runtime::Tokio;
 use futures_core::Future;
-use headers::Host;
-use http::{Method, Request, Response, StatusCode};
+use http::{
+    uri::{Authority, Scheme},
+    Method, Request, Response,
+};
 
 pub use super::node::*;
 use std::fs::Metadata;
@@ -43,18 +44,42 @@ where
 {
     dale_fs::FileSystem::<Tokio>::root_with(path, filetypes)
         .and(filters::ext())
-        .and(filters::header::optional())
-        .map(
-            |path: Node, mount_path: Option<crate::mount::MountPath>, host: Option<Host>| {
-                let host = host
-                    .map(|m| m.to_string())
-                    .unwrap_or_else(|| "localhost".to_string());
-                SerdeNode::from(path, &host, &mount_path.map(|m| m.to_string()))
+        .and(Origin)
+        .and_then(
+            |path: Node,
+             mount_path: Option<crate::mount::MountPath>,
+             scheme: Scheme,
+             host: Authority| {
+                let mount = mount_path.map(|m| m.to_string());
+                std::future::ready(
+                    SerdeNode::from(path, &scheme, &host, &mount).map_err(Error::new),
+                )
             },
         )
         .err_into()
 }
 
+/// Scheme and host for links, preferring what a trusted proxy reported.
+/// Fails with `400 Bad Request` when they don't parse.
+#[derive(Clone, Copy)]
+struct Origin;
+
+impl<B> Service<Request<B>> for Origin {
+    type Output = Outcome<(Request<B>, (Scheme, Authority)), Error, Request<B>>;
+
+    type Future = std::future::Ready<Self::Output>;
+
+    fn call(&self, req: Request<B>) -> Self::Future {
+        std::future::ready(match forwarded::origin(&req) {
+            Ok((scheme, host)) => {
+                let host = host.unwrap_or_else(|| Authority::from_static("localhost"));
+                Outcome::Success((req, (scheme, host)))
+            }
+            Err(err) => Outcome::Failure(err.into()),
+        })
+    }
+}
+
 pub fn file<B>(
     path: impl Into<PathBuf>,
 ) -> impl Service<Request<B>, Future = impl Future + Send, Output = crate::Outcome<B>> + Clone
@@ -69,13 +94,12 @@ where
         .and(file_options())
         .then(
             |(req, (node, meta, mime, options)): (Request<B>, (_, Metadata, _, _))| async move {
+                let mut resp = file_conditional(node, mime, meta, options)?;
                 if req.method() == Method::HEAD {
-                    return Ok(Response::with(StatusCode::NO_CONTENT)
-                        .set(headers::ContentType::from(mime))
-                        .set(headers::ContentLength(meta.len())));
+                    *resp.body_mut() = B::empty();
                 }
 
-                file_conditional(node, mime, meta, options)
+                Ok::<_, Error>(resp)
             },
         )
         .err_into()
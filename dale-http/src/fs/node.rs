@@ -1,5 +1,8 @@
 use dale_fs::{Node, RelativePathBuf};
-use http::Uri;
+use http::{
+    uri::{Authority, Scheme},
+    Uri,
+};
 use std::path::Path;
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize))]
@@ -25,8 +28,15 @@ pub enum SerdeNode {
 }
 
 impl SerdeNode {
-    fn create_uri(host: &str, path: &str, mount: &Option<String>) -> Uri {
-        let mut uri = Uri::builder().scheme("http").authority(host);
+    fn create_uri(
+        scheme: &Scheme,
+        host: &Authority,
+        path: &str,
+        mount: &Option<String>,
+    ) -> Result<Uri, http::Error> {
+        let mut uri = Uri::builder()
+            .scheme(scheme.clone())
+            .authority(host.clone());
 
         if let Some(mount) = mount {
             let path = Path::new(mount).join(path);
@@ -35,26 +45,32 @@ impl SerdeNode {
             uri = uri.path_and_query(path)
         }
 
-        uri.build().unwrap()
+        uri.build()
     }
-    pub(crate) fn from(node: Node, host: &str, mount: &Option<String>) -> SerdeNode {
-        match node {
+
+    pub(crate) fn from(
+        node: Node,
+        scheme: &Scheme,
+        host: &Authority,
+        mount: &Option<String>,
+    ) -> Result<SerdeNode, http::Error> {
+        let node = match node {
             Node::Dir(dir) => {
                 // let path = dir.path.into_os_string().to_string_lossy().to_string();
-                let uri = SerdeNode::create_uri(host, dir.path.as_str(), mount);
+                let uri = SerdeNode::create_uri(scheme, host, dir.path.as_str(), mount)?;
                 SerdeNode::Dir {
                     path: dir.path,
                     href: uri,
                     children: dir
                         .children
                         .into_iter()
-                        .map(|m| SerdeNode::from(m, host, mount))
-                        .collect(),
+                        .map(|m| SerdeNode::from(m, scheme, host, mount))
+                        .collect::<Result<_, _>>()?,
                 }
             }
             Node::File(file) => {
                 // let path = file.path.into_os_string().to_string_lossy().to_string();
-                let uri = SerdeNode::create_uri(host, file.path.as_str(), mount);
+                let uri = SerdeNode::create_uri(scheme, host, file.path.as_str(), mount)?;
                 SerdeNode::File {
                     path: file.path,
                     href: uri,
@@ -62,7 +78,9 @@ impl SerdeNode {
                     size: file.meta.len(),
                 }
             }
-        }
+        };
+
+        Ok(node)
     }
 }
 
@@ -1,19 +1,34 @@
-use crate::types::Reply;
+use crate::{
+    rejection::{self, Rejections},
+    types::Reply,
+    Error,
+};
 #[cfg(feature = "stream")]
 use bytes::Bytes;
 use dale::{IntoOutcome, Outcome, Service};
 use futures_core::ready;
 #[cfg(feature = "stream")]
 use futures_core::Stream;
-use http::StatusCode;
+use http::Extensions;
 use hyper::{service::Service as HyperService, Body, Request, Response};
 use pin_project_lite::pin_project;
-use std::{convert::Infallible, future::Future};
+use std::{convert::Infallible, future::Future, net::SocketAddr, sync::Arc};
 use std::{
     pin::Pin,
     task::{Context, Poll},
 };
 
+/// Answers requests the service passed on with `Next`.
+pub type Fallback = Arc<dyn Fn(Request<Body>) -> Response<Body> + Send + Sync>;
+
+type Extend = Arc<dyn Fn(&mut Extensions) + Send + Sync>;
+
+/// The default fallback: the preferred rejection status, or `404 Not Found`,
+/// as plain text. See [`rejection::respond`].
+pub fn not_found(req: Request<Body>) -> Response<Body> {
+    rejection::respond(&req)
+}
+
 pub fn make<T>(service: T) -> MakeTaskHyperService<T>
 where
     T: Service<Request<Body>>,
@@ -24,11 +39,65 @@ where
 
 pub struct MakeTaskHyperService<T> {
     task: T,
+    fallback: Fallback,
 }
 
 impl<T> MakeTaskHyperService<T> {
     pub fn new(task: T) -> MakeTaskHyperService<T> {
-        MakeTaskHyperService { task: task }
+        MakeTaskHyperService {
+            task,
+            fallback: Arc::new(not_found),
+        }
+    }
+
+    /// Replace the response for requests passed on with `Next`.
+    pub fn fallback<F>(mut self, fallback: F) -> Self
+    where
+        F: Fn(Request<Body>) -> Response<Body> + Send + Sync + 'static,
+    {
+        self.fallback = Arc::new(fallback);
+        self
+    }
+
+    /// The service for a single connection. `remote_addr` is inserted into
+    /// the extensions of every request.
+    pub fn service(&self, remote_addr: Option<SocketAddr>) -> DaleHyperService<T>
+    where
+        T: Clone,
+    {
+        match remote_addr {
+            Some(addr) => self.service_with(addr),
+            None => DaleHyperService {
+                service: self.task.clone(),
+                fallback: self.fallback.clone(),
+                extend: None,
+            },
+        }
+    }
+
+    /// The service for a single connection, inserting a clone of
+    /// `extension` into the extensions of every request.
+    pub fn service_with<E>(&self, extension: E) -> DaleHyperService<T>
+    where
+        T: Clone,
+        E: Clone + Send + Sync + 'static,
+    {
+        DaleHyperService {
+            service: self.task.clone(),
+            fallback: self.fallback.clone(),
+            extend: Some(Arc::new(move |extensions: &mut Extensions| {
+                extensions.insert(extension.clone());
+            })),
+        }
+    }
+}
+
+impl<T: Clone> Clone for MakeTaskHyperService<T> {
+    fn clone(&self) -> Self {
+        MakeTaskHyperService {
+            task: self.task.clone(),
+            fallback: self.fallback.clone(),
+        }
     }
 }
 
@@ -47,9 +116,7 @@ where
 
     fn call(&mut self, _cx: &'t Ctx) -> Self::Future {
         MakeTaskHyperServiceFuture {
-            service: Some(DaleHyperService {
-                service: self.task.clone(),
-            }),
+            service: Some(self.service(None)),
         }
     }
 }
@@ -69,16 +136,19 @@ impl<S> Future for MakeTaskHyperServiceFuture<S> {
 
 pub struct DaleHyperService<T> {
     service: T,
+    fallback: Fallback,
+    extend: Option<Extend>,
 }
 
 impl<T> HyperService<Request<Body>> for DaleHyperService<T>
 where
     T: Send + Service<Request<Body>> + Clone + 'static,
     <T::Output as IntoOutcome<Request<Body>>>::Success: Reply<Body>,
+    <T::Output as IntoOutcome<Request<Body>>>::Failure: Into<Error>,
 {
     type Response = Response<Body>;
 
-    type Error = <T::Output as IntoOutcome<Request<Body>>>::Failure; //<TowerService<T> as HyperService<Request<Body>>>::Error;
+    type Error = Infallible;
 
     type Future = DaleHyperServiceFuture<T>;
 
@@ -86,9 +156,19 @@ where
         Poll::Ready(Ok(()))
     }
 
-    fn call(&mut self, req: Request<Body>) -> Self::Future {
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        if let Some(extend) = &self.extend {
+            extend(req.extensions_mut());
+        }
+        if req.extensions().get::<Rejections>().is_none() {
+            req.extensions_mut().insert(Rejections::default());
+        }
+
         let fut = self.service.call(req);
-        DaleHyperServiceFuture { future: fut }
+        DaleHyperServiceFuture {
+            future: fut,
+            fallback: self.fallback.clone(),
+        }
     }
 }
 
@@ -96,28 +176,25 @@ pin_project! {
     pub struct DaleHyperServiceFuture<S> where S: Service<Request<Body>> {
         #[pin]
         future: S::Future,
+        fallback: Fallback,
     }
 }
 impl<S> Future for DaleHyperServiceFuture<S>
 where
     S: Service<Request<Body>>,
     <S::Output as IntoOutcome<Request<Body>>>::Success: Reply<Body>,
+    <S::Output as IntoOutcome<Request<Body>>>::Failure: Into<Error>,
 {
-    type Output = Result<Response<Body>, <S::Output as IntoOutcome<Request<Body>>>::Failure>;
+    type Output = Result<Response<Body>, Infallible>;
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.project();
         let resp = match ready!(this.future.poll(cx)).into_outcome() {
-            Outcome::Failure(err) => Err(err),
-            Outcome::Next(_) => {
-                let mut resp = Response::new(Body::from(StatusCode::NOT_FOUND.to_string()));
-                *resp.status_mut() = StatusCode::NOT_FOUND;
-
-                Ok(resp)
-            }
-            Outcome::Success(success) => Ok(success.into_response()),
+            Outcome::Failure(err) => err.into().into_response(),
+            Outcome::Next(req) => (this.fallback)(req),
+            Outcome::Success(success) => success.into_response(),
         };
 
-        Poll::Ready(resp)
+        Poll::Ready(Ok(resp))
     }
 }
 
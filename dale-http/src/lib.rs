@@ -1,25 +1,36 @@
 #![allow(opaque_hidden_inferred_bound)]
 
-mod body;
+pub mod body;
+pub mod cache;
+pub mod catch_panic;
+pub mod coalesce;
 pub mod common;
 #[cfg(feature = "serde")]
 pub mod encoder;
 pub mod error;
 pub mod filters;
+pub mod forwarded;
 #[cfg(feature = "fs")]
 pub mod fs;
 mod modifier;
 mod modifiers;
 pub mod mount;
+pub mod rejection;
 pub mod reply_impl;
 mod request_ext;
 #[cfg(feature = "router")]
 pub mod router;
 mod service_ext;
+pub mod split;
+pub mod test;
 mod types;
+#[cfg(feature = "router")]
+pub mod vhost;
 
 #[cfg(feature = "hyper")]
 pub mod hyper;
+#[cfg(feature = "server")]
+pub mod server;
 
 pub use bytes::{self, Bytes};
 pub use http::{self, HeaderMap, HeaderValue, Method, Request, Response, StatusCode, Uri};
@@ -27,7 +38,7 @@ pub use mime;
 
 pub use self::{
     body::Body,
-    error::{Error, KnownError, Result},
+    error::{Error, ErrorHandler, KnownError, Result},
     mount::{mount, Mount},
     request_ext::*,
     types::Reply,
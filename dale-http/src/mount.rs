@@ -1,4 +1,4 @@
-use dale::{IntoOutcome, Middleware, Outcome, Service};
+use dale::{Describe, Description, IntoOutcome, Middleware, Outcome, Service};
 use futures_core::ready;
 use http::{Request, Uri};
 use pin_project_lite::pin_project;
@@ -11,6 +11,8 @@ use std::{
     task::{Context, Poll},
 };
 
+use crate::rejection::{Rejection, Rejections};
+
 pub fn mount<S: ToString, T, B>(path: S, task: T) -> MountTask<T, B>
 where
     T: Service<Request<B>> + Clone,
@@ -96,6 +98,17 @@ where
     }
 }
 
+impl<T, B> Describe for MountTask<T, B>
+where
+    T: Describe,
+{
+    fn describe(&self) -> Description {
+        Description::new("mount")
+            .with_detail(&self.path)
+            .with_child(self.task.describe())
+    }
+}
+
 pin_project! {
     pub struct MountFuture<T, B>
     where T: Service<Request<B>>
@@ -160,6 +173,7 @@ where
                         ensure_mount(&mut req, path[0..path.len() - 1].to_string());
                         (task.call(req), url)
                     } else {
+                        Rejections::record(&mut req, Rejection::not_found());
                         return Poll::Ready(Outcome::Next(req));
                     }
                 }
@@ -201,8 +215,13 @@ impl MountPath {
             .push(path.to_string());
     }
 
+    /// The path the request was mounted under.
+    pub fn prefix(&self) -> String {
+        self.0.join("")
+    }
+
     pub fn real_path<B>(&self, req: &Request<B>) -> String {
-        let mut out = self.0.join("");
+        let mut out = self.prefix();
         out.push_str(req.uri().path());
         out
     }
@@ -269,3 +288,34 @@ fn ensure_mount<B>(req: &mut Request<B>, path: String) {
         .0
         .push(path);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dale_test::{assert_next, assert_success, MockService};
+
+    #[test]
+    fn test_mount() {
+        let mock = MockService::<Request<()>, (), ()>::new();
+        mock.push_success(()).push_next();
+
+        let service = mount("/api", mock.clone());
+
+        let req = Request::get("/api/users?page=2").body(()).unwrap();
+        assert_success!(service, req);
+
+        let req = mock.take_requests().remove(0);
+        assert_eq!(req.uri(), "/users?page=2");
+        assert_eq!(
+            req.extensions().get::<MountPath>().unwrap().real_path(&req),
+            "/api/users"
+        );
+
+        let req = Request::get("/api/users").body(()).unwrap();
+        assert_eq!(assert_next!(service, req).uri(), "/api/users");
+
+        let req = Request::get("/other").body(()).unwrap();
+        assert_next!(service, req);
+        assert_eq!(mock.calls(), 2);
+    }
+}
@@ -0,0 +1,332 @@
+//! Rejection reasons for requests that fell through with `Outcome::Next`.
+//!
+//! Filters record why they passed a request on when tracking is enabled for
+//! it. The hyper adapter and the test client enable it for every request;
+//! elsewhere, wrap a service with [`TrackRejections`]. Because the list travels with
+//! the request through every `or` alternative, the reasons from all branches
+//! end up in the same place, and [`Rejections::preferred`] ranks them the way
+//! warp does: `404` loses to `405`, which loses to any other status, and
+//! otherwise the higher status wins.
+//!
+//! A method filter only reports `405` when a path filter matched earlier in
+//! the same branch, and then only its own method goes into `Allow`. Any other
+//! rejection ends the branch. When the method is checked before the path, the
+//! branch can't tell whether its path would have matched, so it reports
+//! nothing and the request ends up `404` unless another branch knows better.
+use crate::Body;
+use dale::{Middleware, Service};
+use http::{header, HeaderValue, Method, Request, Response, StatusCode};
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejection {
+    status: StatusCode,
+    reason: Cow<'static, str>,
+    allow: Vec<Method>,
+}
+
+impl Rejection {
+    pub fn new(status: StatusCode, reason: impl Into<Cow<'static, str>>) -> Rejection {
+        Rejection {
+            status,
+            reason: reason.into(),
+            allow: Vec::new(),
+        }
+    }
+
+    pub fn not_found() -> Rejection {
+        Rejection::new(StatusCode::NOT_FOUND, "not found")
+    }
+
+    pub fn method_not_allowed() -> Rejection {
+        Rejection::new(StatusCode::METHOD_NOT_ALLOWED, "method not allowed")
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    /// Methods the path would have accepted, for the `Allow` header of a
+    /// `405 Method Not Allowed`.
+    pub fn allow(&self) -> &[Method] {
+        &self.allow
+    }
+
+    pub fn with_allow(mut self, methods: impl IntoIterator<Item = Method>) -> Rejection {
+        self.allow.extend(methods);
+        self
+    }
+
+    fn rank(&self) -> (u8, u16) {
+        match self.status {
+            StatusCode::NOT_FOUND => (0, 0),
+            StatusCode::METHOD_NOT_ALLOWED => (1, 0),
+            status => (2, status.as_u16()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Rejections {
+    list: Vec<Rejection>,
+    path_matched: bool,
+}
+
+impl Rejections {
+    /// Record a rejection on the request, if tracking is enabled for it.
+    /// Anything but a `405` ends the current branch, see
+    /// [`Rejections::path_matched`].
+    pub fn record<B>(req: &mut Request<B>, rejection: Rejection) {
+        if let Some(rejections) = req.extensions_mut().get_mut::<Rejections>() {
+            if rejection.status != StatusCode::METHOD_NOT_ALLOWED {
+                rejections.path_matched = false;
+            }
+            rejections.list.push(rejection);
+        }
+    }
+
+    /// Note that a path filter matched the request, so method mismatches
+    /// later in the same branch count as `405 Method Not Allowed`.
+    pub fn path_matched<B>(req: &mut Request<B>) {
+        if let Some(rejections) = req.extensions_mut().get_mut::<Rejections>() {
+            rejections.path_matched = true;
+        }
+    }
+
+    /// Record a method mismatch, if a path filter matched earlier in the
+    /// current branch.
+    pub fn record_method<B>(req: &mut Request<B>, allow: Method) {
+        if let Some(rejections) = req.extensions_mut().get_mut::<Rejections>() {
+            if rejections.path_matched {
+                rejections
+                    .list
+                    .push(Rejection::method_not_allowed().with_allow([allow]));
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Rejection> {
+        self.list.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// The most specific rejection recorded. Ties go to the earliest.
+    pub fn preferred(&self) -> Option<&Rejection> {
+        self.list.iter().fold(None, |best, next| match best {
+            Some(best) if best.rank() >= next.rank() => Some(best),
+            _ => Some(next),
+        })
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.preferred()
+            .map(|m| m.status())
+            .unwrap_or(StatusCode::NOT_FOUND)
+    }
+
+    /// The methods allowed by all `405` rejections, without duplicates.
+    pub fn allow(&self) -> Vec<Method> {
+        let mut allow: Vec<Method> = Vec::new();
+        for rejection in &self.list {
+            if rejection.status != StatusCode::METHOD_NOT_ALLOWED {
+                continue;
+            }
+            for method in &rejection.allow {
+                if !allow.contains(method) {
+                    allow.push(method.clone());
+                }
+            }
+        }
+        allow
+    }
+}
+
+/// The response to a request every service passed on: the preferred
+/// rejection status, or `404 Not Found`, as plain text. A `405` lists the
+/// allowed methods in the `Allow` header.
+pub fn respond<B: Body, T>(req: &Request<T>) -> Response<B> {
+    let rejections = req.extensions().get::<Rejections>();
+    let status = rejections
+        .map(|m| m.status())
+        .unwrap_or(StatusCode::NOT_FOUND);
+
+    let mut resp = Response::new(B::from_bytes(status.to_string().into_bytes()));
+    *resp.status_mut() = status;
+
+    if let Some(rejections) = rejections.filter(|_| status == StatusCode::METHOD_NOT_ALLOWED) {
+        let allow = rejections
+            .allow()
+            .iter()
+            .map(Method::as_str)
+            .collect::<Vec<_>>()
+            .join(", ");
+        if let Ok(allow) = HeaderValue::from_str(&allow) {
+            if !allow.is_empty() {
+                resp.headers_mut().insert(header::ALLOW, allow);
+            }
+        }
+    }
+
+    resp
+}
+
+/// Middleware enabling rejection tracking for every request passing through.
+#[derive(Debug, Clone, Copy)]
+pub struct TrackRejections;
+
+impl<B, T> Middleware<Request<B>, T> for TrackRejections
+where
+    T: Service<Request<B>>,
+{
+    type Service = TrackRejectionsService<T>;
+
+    fn wrap(&self, service: T) -> Self::Service {
+        TrackRejectionsService { service }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct TrackRejectionsService<T> {
+    service: T,
+}
+
+impl<B, T> Service<Request<B>> for TrackRejectionsService<T>
+where
+    T: Service<Request<B>>,
+{
+    type Output = T::Output;
+    type Future = T::Future;
+
+    fn call(&self, mut req: Request<B>) -> Self::Future {
+        if req.extensions().get::<Rejections>().is_none() {
+            req.extensions_mut().insert(Rejections::default());
+        }
+        self.service.call(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rejections(list: &[StatusCode]) -> Rejections {
+        Rejections {
+            list: list
+                .iter()
+                .map(|status| Rejection::new(*status, ""))
+                .collect(),
+            path_matched: false,
+        }
+    }
+
+    #[test]
+    fn not_found_ranks_lowest() {
+        let r = rejections(&[StatusCode::METHOD_NOT_ALLOWED, StatusCode::NOT_FOUND]);
+        assert_eq!(r.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[test]
+    fn method_needs_path_match() {
+        let mut req = Request::new(());
+        req.extensions_mut().insert(Rejections::default());
+        let status = |req: &Request<()>| req.extensions().get::<Rejections>().unwrap().status();
+
+        Rejections::record_method(&mut req, Method::GET);
+        assert_eq!(status(&req), StatusCode::NOT_FOUND);
+
+        Rejections::path_matched(&mut req);
+        Rejections::record_method(&mut req, Method::POST);
+        Rejections::record_method(&mut req, Method::PUT);
+        Rejections::record(&mut req, Rejection::not_found());
+        Rejections::record_method(&mut req, Method::DELETE);
+        assert_eq!(status(&req), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(
+            req.extensions().get::<Rejections>().unwrap().allow(),
+            [Method::POST, Method::PUT]
+        );
+    }
+
+    #[test]
+    fn method_not_allowed_loses_to_others() {
+        let r = rejections(&[
+            StatusCode::NOT_FOUND,
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            StatusCode::METHOD_NOT_ALLOWED,
+        ]);
+        assert_eq!(r.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[test]
+    fn empty_is_not_found() {
+        assert_eq!(Rejections::default().status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn record_requires_tracking() {
+        let mut req = Request::new(());
+        Rejections::record(&mut req, Rejection::method_not_allowed());
+        assert!(req.extensions().get::<Rejections>().is_none());
+
+        req.extensions_mut().insert(Rejections::default());
+        Rejections::path_matched(&mut req);
+        Rejections::record(&mut req, Rejection::method_not_allowed());
+        assert_eq!(
+            req.extensions().get::<Rejections>().map(|m| m.status()),
+            Some(StatusCode::METHOD_NOT_ALLOWED)
+        );
+    }
+
+    #[tokio::test]
+    async fn method_rejections() {
+        use crate::{
+            body::BoxBody,
+            filters::{get, path, post},
+            test::request,
+        };
+        use dale::ServiceExt;
+
+        let service = TrackRejections.wrap(
+            get()
+                .and(path("/a"))
+                .map(|| "a")
+                .or(path("/b").and(post()).map(|| "b"))
+                .unify(),
+        );
+
+        let resp = request().path("/nope").send(&service).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let resp = request().path("/b").send::<_, BoxBody>(&service).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(resp.header("allow"), Some("POST"));
+
+        // The GET of the `/a` branch stays out of `Allow` for `/b`.
+        let resp = request()
+            .method(Method::PUT)
+            .path("/b")
+            .send::<_, BoxBody>(&service)
+            .await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(resp.header("allow"), Some("POST"));
+
+        let service = path("/c")
+            .and(get().map(|| "get").or(post().map(|| "post")).unify())
+            .or(get().and(path("/d")).map(|| "d"))
+            .unify();
+
+        let resp = request()
+            .method(Method::PUT)
+            .path("/c")
+            .send::<_, BoxBody>(&service)
+            .await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(resp.header("allow"), Some("GET, POST"));
+    }
+}
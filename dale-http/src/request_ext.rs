@@ -63,6 +63,11 @@ pub trait RequestExt<B>: sealed::Sealed {
     #[cfg(feature = "router")]
     fn params(&self) -> &crate::router::Params;
 
+    /// The path of the route named `name` in the router dispatching this
+    /// request, below the path the router is mounted under.
+    #[cfg(feature = "router")]
+    fn url_for(&self, name: &str, params: &[(&str, &str)]) -> Result<String, crate::Error>;
+
     #[cfg(feature = "serde")]
     fn query_params<'de, S: serde::de::Deserialize<'de>>(&'de self) -> Result<S, serde_qs::Error>;
 }
@@ -122,6 +127,21 @@ impl<B> RequestExt<B> for Request<B> {
         self.extensions().get().unwrap_or(&PARAMS)
     }
 
+    #[cfg(feature = "router")]
+    fn url_for(&self, name: &str, params: &[(&str, &str)]) -> Result<String, crate::Error> {
+        let path = self
+            .extensions()
+            .get::<crate::router::Urls>()
+            .ok_or_else(|| crate::Error::new(format!("no route named {}", name)))?
+            .url_for(name, params)?;
+
+        Ok(match self.extensions().get::<crate::mount::MountPath>() {
+            Some(mount) if path == "/" => format!("{}/", mount.prefix()),
+            Some(mount) => format!("{}{}", mount.prefix(), path),
+            None => path,
+        })
+    }
+
     #[cfg(feature = "serde")]
     fn query_params<'de, S: serde::de::Deserialize<'de>>(&'de self) -> Result<S, serde_qs::Error> {
         serde_qs::from_str(self.uri().query().unwrap_or(""))
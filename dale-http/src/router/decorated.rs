@@ -1,29 +1,30 @@
-use super::{route::Route, router::RouterService, routing::Routing, Params, Router};
-use crate::{error::Error, Body, Outcome, Reply};
-use dale::{
-    boxed::BoxFuture, BoxService, IntoOutcome, IntoService, Middleware, Service, ServiceExt,
-    ServiceFailure, ServiceSuccess,
+use super::{
+    route::{Route, RouteInfo, RouteTable},
+    router::{LocalRouterService, RouterService},
+    routing::{IntoRouteService, LocalRouteService, RouteMiddleware, RouteService, Routing},
+    Router,
 };
-use http::{Method, Request, Response, StatusCode};
-use router::{AsSegments, Router as LibRouter};
-use std::{convert::Infallible, sync::Arc};
+use crate::Body;
+use dale::{IntoService, Service};
+use http::{Method, Request};
+use router::AsSegments;
+use std::convert::Infallible;
 
-pub struct DecoratedRouter<B, M> {
-    router: Router<B>,
+pub struct DecoratedRouter<B, M, S = RouteService<B>> {
+    router: Router<B, S>,
     middleware: M,
 }
 
-impl<B, M> DecoratedRouter<B, M> {
-    pub fn new(middleware: M, router: Router<B>) -> DecoratedRouter<B, M>
+impl<B, M, S> DecoratedRouter<B, M, S>
+where
+    S: Service<Request<B>>,
+{
+    pub fn new(middleware: M, router: Router<B, S>) -> DecoratedRouter<B, M, S>
     where
-        B: Send + 'static,
-        M: Middleware<Request<B>, BoxService<'static, Request<B>, Response<B>, Error>> + Clone,
-        M::Service: Service<Request<B>> + Send + Sync + 'static,
-        <M::Service as Service<Request<B>>>::Future: Send,
-        ServiceSuccess<Request<B>, M::Service>: Reply<B> + Send,
-        ServiceFailure<Request<B>, M::Service>: Into<Error>,
+        M: RouteMiddleware<B, S> + Clone,
     {
         let mut new_router = Router::default();
+        new_router.auto_options(router.auto_options);
 
         new_router.extend(
             router
@@ -36,17 +37,37 @@ impl<B, M> DecoratedRouter<B, M> {
             middleware,
         }
     }
+
+    /// See [`Router::name`].
+    pub fn name(&mut self, name: impl Into<String>) -> &mut Self {
+        self.router.name(name);
+        self
+    }
+
+    /// See [`Router::routes`].
+    pub fn routes(&self) -> impl Iterator<Item = &RouteInfo> {
+        self.router.routes()
+    }
+
+    /// See [`Router::route_table`].
+    pub fn route_table(&self) -> RouteTable<'_> {
+        self.router.route_table()
+    }
+
+    /// See [`Router::auto_options`].
+    pub fn auto_options(&mut self, enabled: bool) -> &mut Self {
+        self.router.auto_options(enabled);
+        self
+    }
 }
 
-impl<B, M> Routing<B> for DecoratedRouter<B, M>
+impl<B, M, T> Routing<B> for DecoratedRouter<B, M, T>
 where
-    B: Send + 'static,
-    M: Middleware<Request<B>, BoxService<'static, Request<B>, Response<B>, Error>> + Clone,
-    M::Service: Service<Request<B>> + Send + Sync + 'static,
-    <M::Service as Service<Request<B>>>::Future: Send,
-    ServiceSuccess<Request<B>, M::Service>: Reply<B> + Send,
-    ServiceFailure<Request<B>, M::Service>: Into<Error>,
+    T: Service<Request<B>>,
+    M: RouteMiddleware<B, T> + Clone,
 {
+    type Service = T;
+
     fn register<'a, P, S>(
         &mut self,
         method: Method,
@@ -55,24 +76,13 @@ where
     ) -> Result<&mut Self, P::Error>
     where
         P: AsSegments<'a> + 'a,
-        B: 'static,
-        S: Service<Request<B>> + Send + Sync + 'static,
-        S::Future: Send,
-        <S::Output as IntoOutcome<Request<B>>>::Success: Reply<B> + Send,
-        <S::Output as IntoOutcome<Request<B>>>::Failure: Into<Error>,
+        S: IntoRouteService<B, T>,
     {
-        let service_box = service
-            .then(
-                |resp: <S::Output as IntoOutcome<Request<B>>>::Success| async move {
-                    let resp = resp.into_response();
-                    Result::<_, Error>::Ok(resp)
-                },
-            )
-            .err_into()
-            .boxed();
-
         self.router
-            .register(method, path, self.middleware.wrap(service_box))?;
+            .register_route(method, path, service.into_route_service())?;
+        let middleware = &self.middleware;
+        self.router
+            .map_pending(|route| route.wrap(middleware.clone()));
 
         Ok(self)
     }
@@ -80,7 +90,7 @@ where
     fn mount<'a, 'b, P, I>(&mut self, path: P, router: I) -> Result<&mut Self, P::Error>
     where
         P: AsSegments<'a> + 'a,
-        I: IntoIterator<Item = router::Route<'b, Route<B>>>,
+        I: IntoIterator<Item = router::Route<'b, Route<B, T>>>,
     {
         self.router.mount(
             path,
@@ -94,7 +104,7 @@ where
 
     fn extend<'a, I>(&mut self, router: I) -> &mut Self
     where
-        I: IntoIterator<Item = router::Route<'a, Route<B>>>,
+        I: IntoIterator<Item = router::Route<'a, Route<B, T>>>,
     {
         self.router.extend(
             router
@@ -105,15 +115,10 @@ where
         self
     }
 
-    fn wrap<M1>(self, middleware: M1) -> DecoratedRouter<B, M1>
+    fn wrap<M1>(self, middleware: M1) -> DecoratedRouter<B, M1, T>
     where
         Self: Sized,
-        B: Send + 'static,
-        M1: Middleware<Request<B>, BoxService<'static, Request<B>, Response<B>, Error>> + Clone,
-        M1::Service: Service<Request<B>> + Send + Sync + 'static,
-        <M1::Service as Service<Request<B>>>::Future: Send,
-        ServiceSuccess<Request<B>, M1::Service>: Reply<B> + Send,
-        ServiceFailure<Request<B>, M1::Service>: Into<Error>,
+        M1: RouteMiddleware<B, T> + Clone,
     {
         DecoratedRouter::new(middleware, self.router)
     }
@@ -128,9 +133,18 @@ impl<B: Body + Send + Sync + 'static, M> IntoService<Request<B>> for DecoratedRo
     }
 }
 
-impl<B, M> IntoIterator for DecoratedRouter<B, M> {
-    type IntoIter = router::router::IntoIter<Route<B>>;
-    type Item = router::Route<'static, Route<B>>;
+impl<B: Body + 'static, M> IntoService<Request<B>> for DecoratedRouter<B, M, LocalRouteService<B>> {
+    type Error = Infallible;
+    type Service = LocalRouterService<B>;
+
+    fn into_service(self) -> Result<Self::Service, Self::Error> {
+        self.router.into_service()
+    }
+}
+
+impl<B, M, S> IntoIterator for DecoratedRouter<B, M, S> {
+    type IntoIter = router::router::IntoIter<Route<B, S>>;
+    type Item = router::Route<'static, Route<B, S>>;
     fn into_iter(self) -> Self::IntoIter {
         self.router.into_iter()
     }
@@ -3,9 +3,15 @@ mod params;
 mod route;
 mod router;
 mod routing;
+mod urls;
 
 pub use self::{
-    decorated::DecoratedRouter, params::*, route::Route, router::Router, routing::Routing,
+    decorated::DecoratedRouter,
+    params::*,
+    route::{LocalRoute, Route, RouteInfo, RouteTable},
+    router::{LocalRouter, LocalRouterService, Router, RouterService},
+    routing::{IntoRouteService, LocalRouteService, RouteMiddleware, RouteService, Routing},
+    urls::Urls,
 };
-pub type IntoIter<B> = ::router::router::IntoIter<Route<B>>;
+pub type IntoIter<B, S = RouteService<B>> = ::router::router::IntoIter<Route<B, S>>;
 pub use ::router::{AsSegments, Segments};
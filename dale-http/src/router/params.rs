@@ -1,31 +1,66 @@
+use percent_encoding::percent_decode_str;
 use router::Params as RouteParams;
-use std::collections::HashMap;
+use std::borrow::Cow;
 
-#[derive(Default, Debug)]
+/// Parameters captured by the matched route, percent-decoded.
+#[derive(Default, Debug, Clone)]
 pub struct Params {
-    i: Option<HashMap<String, String>>,
+    i: Vec<(String, String)>,
+    wildcard: Option<String>,
 }
 
 impl Params {
     pub const fn new() -> Params {
-        Params { i: None }
+        Params {
+            i: Vec::new(),
+            wildcard: None,
+        }
     }
 }
 
 impl Params {
     pub fn get(&self, key: &str) -> Option<&str> {
-        self.i.as_ref().and_then(|i| i.get(key).map(|m| m.as_str()))
+        self.i
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Name and value of every parameter, in path order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.i
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.i.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.i.is_empty()
+    }
+
+    /// The rest of the path matched by a trailing `*name` segment.
+    pub fn wildcard(&self) -> Option<&str> {
+        self.wildcard.as_deref().and_then(|name| self.get(name))
+    }
+
+    pub(crate) fn set_wildcard(&mut self, name: &str) {
+        self.wildcard = Some(name.to_string());
     }
 }
 
 impl<'a> RouteParams<'a> for Params {
-    fn set(&mut self, key: std::borrow::Cow<'a, str>, value: std::borrow::Cow<'a, str>) {
-        if self.i.is_none() {
-            self.i = Some(HashMap::default());
+    fn set(&mut self, key: Cow<'a, str>, value: Cow<'a, str>) {
+        let value = match percent_decode_str(&value).decode_utf8() {
+            Ok(decoded) => decoded.into_owned(),
+            Err(_) => value.into_owned(),
+        };
+
+        match self.i.iter_mut().find(|(name, _)| *name == key) {
+            Some(param) => param.1 = value,
+            None => self.i.push((key.into_owned(), value)),
         }
-        self.i
-            .as_mut()
-            .unwrap()
-            .insert(key.to_string(), value.to_string());
     }
 }
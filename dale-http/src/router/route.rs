@@ -1,48 +1,169 @@
-use std::fmt;
-
-use crate::{error::Error, service_ext::HttpServiceExt, Reply};
-use dale::{
-    BoxService, IntoOutcomeExt, Middleware, MiddlewareFnService, Service, ServiceExt,
-    ServiceFailure, ServiceSuccess,
-};
-use futures_core::Future;
-use http::{Method, Request, Response};
-
-pub struct Route<B> {
-    pub(super) service: BoxService<'static, Request<B>, Response<B>, Error>,
+use std::{fmt, marker::PhantomData};
+
+use super::routing::{LocalRouteService, RouteMiddleware, RouteService};
+use http::Method;
+use router::{AsSegments, Segments};
+
+pub struct Route<B, S = RouteService<B>> {
+    pub(super) service: S,
     pub(super) method: Method,
+    pub(super) path: String,
+    pub(super) name: Option<String>,
+    middleware: usize,
+    _body: PhantomData<fn(B)>,
 }
 
-impl<B> fmt::Debug for Route<B> {
+pub type LocalRoute<B> = Route<B, LocalRouteService<B>>;
+
+impl<B, S> fmt::Debug for Route<B, S> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Route")
             .field("method", &self.method)
+            .field("path", &self.path)
+            .field("name", &self.name)
+            .field("middleware", &self.middleware)
             .finish_non_exhaustive()
     }
 }
 
-impl<B> Route<B> {
-    pub(super) fn new(
-        method: Method,
-        service: BoxService<'static, Request<B>, Response<B>, Error>,
-    ) -> Route<B> {
-        Route { service, method }
+impl<B, S> Route<B, S> {
+    pub(super) fn new(method: Method, path: String, service: S) -> Route<B, S> {
+        Route {
+            service,
+            method,
+            path,
+            name: None,
+            middleware: 0,
+            _body: PhantomData,
+        }
     }
 
-    pub fn wrap<M>(mut self, middleware: M) -> Route<B>
+    pub(super) fn info(&self) -> RouteInfo {
+        RouteInfo {
+            method: self.method.clone(),
+            path: self.path.clone(),
+            name: self.name.clone(),
+            middleware: self.middleware,
+        }
+    }
+
+    pub(super) fn prefix(mut self, prefix: &str) -> Route<B, S> {
+        self.path = join_path(prefix, &self.path);
+        self
+    }
+
+    pub fn wrap<M>(mut self, middleware: M) -> Route<B, S>
     where
-        B: Send + 'static,
-        M: Middleware<Request<B>, BoxService<'static, Request<B>, Response<B>, Error>>,
-        M::Service: Service<Request<B>> + Send + Sync + 'static,
-        <M::Service as Service<Request<B>>>::Future: Send,
-        ServiceSuccess<Request<B>, M::Service>: Reply<B> + Send,
-        ServiceFailure<Request<B>, M::Service>: Into<Error>,
+        M: RouteMiddleware<B, S>,
     {
-        self.service = middleware
-            .wrap(self.service)
-            .err_into()
-            .into_response()
-            .boxed();
+        self.service = middleware.wrap_route(self.service);
+        self.middleware += 1;
         self
     }
 }
+
+/// A registered route, as listed by [`Router::routes`](super::Router::routes).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteInfo {
+    pub(crate) method: Method,
+    pub(crate) path: String,
+    pub(crate) name: Option<String>,
+    pub(crate) middleware: usize,
+}
+
+impl RouteInfo {
+    pub fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// The path template, such as `/users/:id`.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// How many route middlewares wrap the service.
+    pub fn middleware(&self) -> usize {
+        self.middleware
+    }
+}
+
+impl fmt::Display for RouteInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.path)?;
+        if let Some(name) = &self.name {
+            write!(f, " ({})", name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Prints routes as an aligned table, one route per line, for startup logs.
+#[derive(Debug, Clone, Copy)]
+pub struct RouteTable<'a> {
+    routes: &'a [RouteInfo],
+}
+
+impl<'a> RouteTable<'a> {
+    pub(crate) fn new(routes: &'a [RouteInfo]) -> RouteTable<'a> {
+        RouteTable { routes }
+    }
+}
+
+impl<'a> fmt::Display for RouteTable<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width =
+            |column: fn(&RouteInfo) -> usize| self.routes.iter().map(column).max().unwrap_or(0);
+        let method = width(|route| route.method.as_str().len());
+        let path = width(|route| route.path.len());
+        let name = width(|route| route.name.as_deref().map_or(1, str::len));
+
+        for route in self.routes {
+            writeln!(
+                f,
+                "{:method$}  {:path$}  {:name$}  {}",
+                route.method.as_str(),
+                route.path,
+                route.name.as_deref().unwrap_or("-"),
+                route.middleware,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+pub(super) fn parse_path<'a, P>(path: P) -> Result<(Segments<'static>, String), P::Error>
+where
+    P: AsSegments<'a> + 'a,
+{
+    let segments = path
+        .as_segments()?
+        .into_iter()
+        .map(|m| m.to_static())
+        .collect::<Vec<_>>();
+
+    let mut template = String::new();
+    for segment in &segments {
+        template.push('/');
+        template.push_str(&segment.to_string());
+    }
+
+    if template.is_empty() {
+        template.push('/');
+    }
+
+    Ok((Segments::new(segments), template))
+}
+
+fn join_path(prefix: &str, path: &str) -> String {
+    let prefix = prefix.trim_end_matches('/');
+    if path == "/" && !prefix.is_empty() {
+        prefix.to_string()
+    } else {
+        format!("{}{}", prefix, path)
+    }
+}
@@ -1,34 +1,142 @@
-use super::{decorated::DecoratedRouter, route::Route, routing::Routing, Params};
-use crate::{error::Error, Body, Outcome, Reply};
+use super::{
+    decorated::DecoratedRouter,
+    route::{parse_path, Route, RouteInfo, RouteTable},
+    routing::{IntoRouteService, LocalRouteService, RouteMiddleware, RouteService, Routing},
+    Params, Urls,
+};
+use crate::{
+    rejection::{Rejection, Rejections},
+    Body, Outcome,
+};
 use dale::{
-    boxed::BoxFuture, BoxService, IntoOutcome, IntoService, Middleware, Service, ServiceExt,
-    ServiceFailure, ServiceSuccess,
+    boxed::{BoxFuture, LocalBoxFuture},
+    Describe, Description, IntoService, Service,
 };
-use http::{Method, Request, Response, StatusCode};
+use http::{header, HeaderValue, Method, Request, Response, StatusCode};
 use router::{AsSegments, Router as LibRouter};
-use std::{convert::Infallible, sync::Arc};
+use std::{convert::Infallible, fmt, rc::Rc, sync::Arc};
 
-#[derive(Debug)]
-pub struct Router<B> {
-    router: LibRouter<Route<B>>,
+pub struct Router<B, S = RouteService<B>> {
+    router: LibRouter<Route<B, S>>,
+    routes: Vec<RouteInfo>,
+    // The route registered last is held back, so it can still be named.
+    pending: Option<router::Route<'static, Route<B, S>>>,
+    urls: Urls,
+    pub(super) auto_options: bool,
 }
 
-impl<B> Default for Router<B> {
-    fn default() -> Self {
-        Router::new()
+impl<B, S> fmt::Debug for Router<B, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Router")
+            .field("router", &self.router)
+            .finish_non_exhaustive()
     }
 }
 
-impl<B> Router<B> {
-    pub fn new() -> Router<B> {
+/// A router for services that aren't `Send`, such as handlers holding `Rc`
+/// or `RefCell` state on a single threaded runtime. Create one with
+/// [`Router::local`].
+pub type LocalRouter<B> = Router<B, LocalRouteService<B>>;
+
+impl<B, S> Default for Router<B, S> {
+    fn default() -> Self {
         Router {
             router: LibRouter::new(),
+            routes: Vec::new(),
+            pending: None,
+            urls: Urls::default(),
+            auto_options: true,
         }
     }
 }
 
-impl<B> Routing<B> for Router<B> {
-    fn register<'a, P, S>(
+impl<B> Router<B> {
+    pub fn new() -> Router<B> {
+        Router::default()
+    }
+
+    /// Create a [`LocalRouter`]. This can't be `LocalRouter::new`, since a
+    /// second `new` would make every un-annotated `Router::new()` ambiguous.
+    pub fn local() -> LocalRouter<B> {
+        Router::default()
+    }
+}
+
+impl<B, S> Router<B, S> {
+    /// Answer `OPTIONS` requests for paths without an `OPTIONS` route with
+    /// the allowed methods. On by default.
+    pub fn auto_options(&mut self, enabled: bool) -> &mut Self {
+        self.auto_options = enabled;
+        self
+    }
+
+    /// Names the route registered last, so links to it can be built with
+    /// [`RequestExt::url_for`](crate::RequestExt::url_for). Names are unique
+    /// across the router and the routers mounted into it.
+    ///
+    /// # Panics
+    ///
+    /// When no route was registered since the last call to `mount` or
+    /// `extend`, or when the name is already taken. A name taken in a mounted
+    /// router panics when the router is turned into a service.
+    pub fn name(&mut self, name: impl Into<String>) -> &mut Self {
+        let name = name.into();
+        assert!(
+            !self
+                .routes
+                .iter()
+                .any(|route| route.name.as_deref() == Some(name.as_str())),
+            "route name {} is already taken",
+            name
+        );
+
+        match (&mut self.pending, self.routes.last_mut()) {
+            (Some(route), Some(info)) => {
+                info.name = Some(name.clone());
+                route.handle.name = Some(name);
+            }
+            _ => panic!("no route to name {}", name),
+        }
+        self
+    }
+
+    /// The registered routes, in registration order.
+    pub fn routes(&self) -> impl Iterator<Item = &RouteInfo> {
+        self.routes.iter()
+    }
+
+    /// The registered routes, printable as a table.
+    pub fn route_table(&self) -> RouteTable<'_> {
+        RouteTable::new(&self.routes)
+    }
+
+    pub(super) fn map_pending(&mut self, func: impl FnOnce(Route<B, S>) -> Route<B, S>) {
+        if let Some(route) = self.pending.take() {
+            let route = route.map(func);
+            if let Some(info) = self.routes.last_mut() {
+                *info = route.handle.info();
+            }
+            self.pending = Some(route);
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Some(route) = self.pending.take() {
+            // Registering parsed `Segments` can't fail: their error type is
+            // `Infallible`, so this stops compiling if that ever changes.
+            if let Err(err) = self.router.register(route.segments, route.handle) {
+                match err {}
+            }
+        }
+    }
+
+    fn freeze(mut self) -> Self {
+        self.flush();
+        self.urls = Urls::new(&self.routes);
+        self
+    }
+
+    pub(super) fn register_route<'a, P>(
         &mut self,
         method: Method,
         path: P,
@@ -36,74 +144,254 @@ impl<B> Routing<B> for Router<B> {
     ) -> Result<&mut Self, P::Error>
     where
         P: AsSegments<'a> + 'a,
-        B: 'static,
-        S: Service<Request<B>> + Send + Sync + 'static,
-        S::Future: Send,
-        <S::Output as IntoOutcome<Request<B>>>::Success: Reply<B> + Send,
-        <S::Output as IntoOutcome<Request<B>>>::Failure: Into<Error>,
     {
-        let service_box = service
-            .then(
-                |resp: <S::Output as IntoOutcome<Request<B>>>::Success| async move {
-                    let resp = resp.into_response();
-                    Result::<_, Error>::Ok(resp)
-                },
-            )
-            .err_into()
-            .boxed();
-
-        self.router
-            .register(path, Route::new(method, service_box))?;
+        let (segments, template) = parse_path(path)?;
+        self.flush();
+        let route = Route::new(method, template, service);
+        self.routes.push(route.info());
+        self.pending = Some(router::Route {
+            segments,
+            handle: route,
+        });
 
         Ok(self)
     }
+}
+
+impl<B, T> Routing<B> for Router<B, T>
+where
+    T: Service<Request<B>>,
+{
+    type Service = T;
+
+    fn register<'a, P, S>(
+        &mut self,
+        method: Method,
+        path: P,
+        service: S,
+    ) -> Result<&mut Self, P::Error>
+    where
+        P: AsSegments<'a> + 'a,
+        S: IntoRouteService<B, T>,
+    {
+        self.register_route(method, path, service.into_route_service())
+    }
 
     fn mount<'a, 'b, P, I>(&mut self, path: P, router: I) -> Result<&mut Self, P::Error>
     where
         P: AsSegments<'a> + 'a,
-        I: IntoIterator<Item = router::Route<'b, Route<B>>>,
+        I: IntoIterator<Item = router::Route<'b, Route<B, T>>>,
     {
-        self.router.mount(path, router)?;
+        let (segments, prefix) = parse_path(path)?;
+
+        self.flush();
+        let routes = &mut self.routes;
+        let router = router.into_iter().map(|route| {
+            route.map(|handle| {
+                let handle = handle.prefix(&prefix);
+                routes.push(handle.info());
+                handle
+            })
+        });
+
+        if let Err(err) = self.router.mount(segments, router) {
+            match err {}
+        }
+
         Ok(self)
     }
 
     fn extend<'a, I>(&mut self, router: I) -> &mut Self
     where
-        I: IntoIterator<Item = router::Route<'a, Route<B>>>,
+        I: IntoIterator<Item = router::Route<'a, Route<B, T>>>,
     {
-        self.router.extend(router);
+        self.flush();
+        let routes = &mut self.routes;
+        self.router.extend(router.into_iter().map(|route| {
+            route.map(|handle| {
+                routes.push(handle.info());
+                handle
+            })
+        }));
         self
     }
 
-    fn wrap<M>(self, middleware: M) -> DecoratedRouter<B, M>
+    fn wrap<M>(self, middleware: M) -> DecoratedRouter<B, M, T>
     where
         Self: Sized,
-        B: Send + 'static,
-        M: Middleware<Request<B>, BoxService<'static, Request<B>, Response<B>, Error>> + Clone,
-        M::Service: Service<Request<B>> + Send + Sync + 'static,
-        <M::Service as Service<Request<B>>>::Future: Send,
-        ServiceSuccess<Request<B>, M::Service>: Reply<B> + Send,
-        ServiceFailure<Request<B>, M::Service>: Into<Error>,
+        M: RouteMiddleware<B, T> + Clone,
     {
         DecoratedRouter::new(middleware, self)
     }
 }
 
+// Shared by `RouterService` and `LocalRouterService`. A generic async fn
+// would read better, but proving its future `Send` trips over the boxed
+// route services.
+macro_rules! dispatch {
+    ($router: expr, $req: expr) => {{
+        let router = $router;
+        let mut req = $req;
+        async move {
+            // Parameters captured further out, such as by `vhost::Hosts`,
+            // stay visible to the route.
+            let outer = req.extensions().get::<Params>().cloned();
+            let outer_urls = req.extensions().get::<Urls>().cloned();
+            let mut params = outer.clone().unwrap_or_default();
+            let found = match router.router.find(req.uri().path(), &mut params) {
+                Some(found) => found,
+                None => {
+                    Rejections::record(&mut req, Rejection::not_found());
+                    return Outcome::Next(req);
+                }
+            };
+
+            let method = req.method().clone();
+
+            let is_head = method == Method::HEAD;
+
+            if let Some(name) = found
+                .first()
+                .and_then(|route| route.path.rsplit('/').next())
+                .and_then(|last| last.strip_prefix('*'))
+            {
+                params.set_wildcard(name);
+            }
+
+            req.extensions_mut().insert(params);
+            req.extensions_mut().insert(router.urls.clone());
+
+            // Dedicated `HEAD` routes come before `GET` routes standing in
+            // for them.
+            let exact = found.iter().filter(|route| route.method == method);
+            let implied = found
+                .iter()
+                .filter(|route| is_head && route.method == Method::GET);
+
+            for next in exact.chain(implied) {
+                match next.service.call(req).await {
+                    Outcome::Next(r) => {
+                        req = r;
+                    }
+                    Outcome::Success(success) => {
+                        if method != next.method && is_head {
+                            return dale::Outcome::Success(strip_body(success));
+                        }
+
+                        return dale::Outcome::Success(success);
+                    }
+                    o => return o,
+                }
+            }
+
+            match outer {
+                Some(params) => req.extensions_mut().insert(params),
+                None => req.extensions_mut().remove::<Params>(),
+            };
+            match outer_urls {
+                Some(urls) => req.extensions_mut().insert(urls),
+                None => req.extensions_mut().remove::<Urls>(),
+            };
+
+            if found
+                .iter()
+                .any(|route| route.method == method || (is_head && route.method == Method::GET))
+            {
+                return Outcome::Next(req);
+            }
+
+            let allow =
+                allowed_methods(found.iter().map(|route| &route.method), router.auto_options);
+            if method == Method::OPTIONS && router.auto_options {
+                return Outcome::Success(options_response(&allow));
+            }
+
+            // Passed on, so a later service can still take the request. The
+            // `405` wins once nothing else does.
+            Rejections::record(&mut req, Rejection::method_not_allowed().with_allow(allow));
+            Outcome::Next(req)
+        }
+    }};
+}
+
+/// Answers `HEAD` with the response to `GET`: same status and headers, no
+/// body. `Content-Length` is filled in when the body size is known.
+fn strip_body<B: Body>(resp: Response<B>) -> Response<B> {
+    let (mut parts, body) = resp.into_parts();
+    if !parts.headers.contains_key(header::CONTENT_LENGTH) {
+        if let Some(len) = body.size_hint().exact() {
+            parts.headers.insert(header::CONTENT_LENGTH, len.into());
+        }
+    }
+    Response::from_parts(parts, B::empty())
+}
+
+/// Methods of the matched routes, with `HEAD` implied by `GET`.
+fn allowed_methods<'a>(methods: impl Iterator<Item = &'a Method>, options: bool) -> Vec<Method> {
+    let mut allow: Vec<Method> = Vec::new();
+    let mut push = |method: &Method| {
+        if !allow.contains(method) {
+            allow.push(method.clone());
+        }
+    };
+
+    for method in methods {
+        push(method);
+        if method == Method::GET {
+            push(&Method::HEAD);
+        }
+    }
+
+    if options {
+        push(&Method::OPTIONS);
+    }
+
+    allow
+}
+
+/// The automatic answer to `OPTIONS`.
+fn options_response<B: Body>(allow: &[Method]) -> Response<B> {
+    let allow = allow
+        .iter()
+        .map(Method::as_str)
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut resp = Response::new(B::empty());
+    *resp.status_mut() = StatusCode::NO_CONTENT;
+    if let Ok(allow) = HeaderValue::from_str(&allow) {
+        resp.headers_mut().insert(header::ALLOW, allow);
+    }
+    resp
+}
+
 impl<B: Body + Send + Sync + 'static> IntoService<Request<B>> for Router<B> {
     type Error = Infallible;
     type Service = RouterService<B>;
 
     fn into_service(self) -> Result<Self::Service, Self::Error> {
         Ok(RouterService {
-            router: self.into(),
+            router: self.freeze().into(),
+        })
+    }
+}
+
+impl<B: Body + 'static> IntoService<Request<B>> for LocalRouter<B> {
+    type Error = Infallible;
+    type Service = LocalRouterService<B>;
+
+    fn into_service(self) -> Result<Self::Service, Self::Error> {
+        Ok(LocalRouterService {
+            router: self.freeze().into(),
         })
     }
 }
 
-impl<B> IntoIterator for Router<B> {
-    type IntoIter = router::router::IntoIter<Route<B>>;
-    type Item = router::Route<'static, Route<B>>;
-    fn into_iter(self) -> Self::IntoIter {
+impl<B, S> IntoIterator for Router<B, S> {
+    type IntoIter = router::router::IntoIter<Route<B, S>>;
+    type Item = router::Route<'static, Route<B, S>>;
+    fn into_iter(mut self) -> Self::IntoIter {
+        self.flush();
         self.router.into_iter()
     }
 }
@@ -121,50 +409,338 @@ impl<B> Clone for RouterService<B> {
     }
 }
 
+impl<B> RouterService<B> {
+    /// See [`Router::routes`].
+    pub fn routes(&self) -> impl Iterator<Item = &RouteInfo> {
+        self.router.routes()
+    }
+
+    /// See [`Router::route_table`].
+    pub fn route_table(&self) -> RouteTable<'_> {
+        self.router.route_table()
+    }
+}
+
 impl<B: Body + Send + Sync + 'static> Service<Request<B>> for RouterService<B> {
     type Output = Outcome<B>;
 
     type Future = BoxFuture<'static, Self::Output>;
 
-    fn call(&self, mut req: Request<B>) -> Self::Future {
-        let router = self.router.clone();
+    fn call(&self, req: Request<B>) -> Self::Future {
+        Box::pin(dispatch!(self.router.clone(), req))
+    }
+}
 
-        Box::pin(async move {
-            let mut params = Params::default();
-            let found = match router.router.find(req.uri().path(), &mut params) {
-                Some(found) => found,
-                None => return Outcome::Next(req),
-            };
+#[derive(Debug)]
+pub struct LocalRouterService<B> {
+    router: Rc<LocalRouter<B>>,
+}
 
-            let method = req.method().clone();
+impl<B> Clone for LocalRouterService<B> {
+    fn clone(&self) -> Self {
+        LocalRouterService {
+            router: self.router.clone(),
+        }
+    }
+}
 
-            let is_head = method == Method::HEAD;
+impl<B> LocalRouterService<B> {
+    /// See [`Router::routes`].
+    pub fn routes(&self) -> impl Iterator<Item = &RouteInfo> {
+        self.router.routes()
+    }
 
-            req.extensions_mut().insert(params);
+    /// See [`Router::route_table`].
+    pub fn route_table(&self) -> RouteTable<'_> {
+        self.router.route_table()
+    }
+}
 
-            for next in found
-                .iter()
-                .filter(|route| route.method == method || (is_head && route.method == Method::GET))
-            {
-                match next.service.call(req).await.into_outcome() {
-                    Outcome::Next(r) => {
-                        req = r;
-                    }
-                    Outcome::Success(mut success) => {
-                        if method != next.method && is_head {
-                            *success.body_mut() = B::empty();
-                            *success.status_mut() = StatusCode::NO_CONTENT;
-                        }
+impl<B: Body + 'static> Service<Request<B>> for LocalRouterService<B> {
+    type Output = Outcome<B>;
 
-                        return dale::Outcome::Success(success);
+    type Future = LocalBoxFuture<'static, Self::Output>;
+
+    fn call(&self, req: Request<B>) -> Self::Future {
+        Box::pin(dispatch!(self.router.clone(), req))
+    }
+}
+
+fn describe<B, S>(router: &Router<B, S>) -> Description {
+    Description::new("router").with_children(router.routes.iter().map(|route| {
+        Description::new("route").with_detail(format_args!("{} {}", route.method, route.path))
+    }))
+}
+
+impl<B> Describe for LocalRouterService<B> {
+    fn describe(&self) -> Description {
+        describe(&self.router)
+    }
+}
+
+impl<B> Describe for RouterService<B> {
+    fn describe(&self) -> Description {
+        describe(&self.router)
+    }
+}
+
+#[cfg(all(test, feature = "hyper"))]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[tokio::test]
+    async fn test_local_router() {
+        let hits = Rc::new(Cell::new(0));
+
+        let mut router = Router::<hyper::Body>::local();
+        router
+            .get("/", {
+                let hits = hits.clone();
+                move |_req: Request<hyper::Body>| {
+                    let hits = hits.clone();
+                    async move {
+                        hits.set(hits.get() + 1);
+                        "Hello, World!"
                     }
-                    o => return o,
                 }
-            }
+            })
+            .unwrap();
 
-            req.extensions_mut().remove::<Params>();
+        let service = router.into_service().unwrap();
 
-            Outcome::Next(req)
-        })
+        let req = Request::get("/").body(hyper::Body::empty()).unwrap();
+        assert!(matches!(service.call(req).await, Outcome::Success(_)));
+
+        let req = Request::get("/missing").body(hyper::Body::empty()).unwrap();
+        assert!(matches!(service.call(req).await, Outcome::Next(_)));
+
+        assert_eq!(hits.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_method_not_allowed() {
+        use crate::{body::BoxBody, test::request};
+        use dale::ServiceExt;
+
+        let mut router = Router::<BoxBody>::new();
+        router
+            .get("/users", |_req: Request<BoxBody>| async move { "list" })
+            .unwrap()
+            .post("/users", |_req: Request<BoxBody>| async move { "create" })
+            .unwrap();
+        let service = router.into_service().unwrap();
+
+        let resp = request()
+            .method(Method::DELETE)
+            .path("/users")
+            .send(&service)
+            .await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(resp.header("allow"), Some("GET, HEAD, POST, OPTIONS"));
+
+        let resp = request()
+            .method(Method::OPTIONS)
+            .path("/users")
+            .send(&service)
+            .await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+        assert_eq!(resp.header("allow"), Some("GET, HEAD, POST, OPTIONS"));
+
+        // A path match with the wrong method is passed on.
+        let mut admin = Router::<BoxBody>::new();
+        admin
+            .delete("/users", |_req: Request<BoxBody>| async move { "deleted" })
+            .unwrap();
+        let combined = service.clone().or(admin.into_service().unwrap()).unify();
+
+        let resp = request()
+            .method(Method::DELETE)
+            .path("/users")
+            .send(&combined)
+            .await;
+        assert_eq!(resp.text(), "deleted");
+
+        let mut router = Router::<BoxBody>::new();
+        router
+            .auto_options(false)
+            .get("/users", |_req: Request<BoxBody>| async move { "list" })
+            .unwrap();
+        let service = router.into_service().unwrap();
+
+        let resp = request()
+            .method(Method::OPTIONS)
+            .path("/users")
+            .send(&service)
+            .await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(resp.header("allow"), Some("GET, HEAD"));
+    }
+
+    #[tokio::test]
+    async fn test_head() {
+        use crate::{body::BoxBody, test::request};
+
+        let mut router = Router::<BoxBody>::new();
+        router
+            .get("/health", |_req: Request<BoxBody>| async move { "ok" })
+            .unwrap()
+            .get("/info", |_req: Request<BoxBody>| async move { "info" })
+            .unwrap()
+            .register(Method::HEAD, "/info", |_req: Request<BoxBody>| async move {
+                let mut resp = Response::new(BoxBody::empty());
+                *resp.status_mut() = StatusCode::ACCEPTED;
+                resp
+            })
+            .unwrap();
+        let service = router.into_service().unwrap();
+
+        let resp = request()
+            .method(Method::HEAD)
+            .path("/health")
+            .send(&service)
+            .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.header("content-length"), Some("2"));
+        assert!(resp.header("content-type").is_some());
+        assert_eq!(resp.text(), "");
+
+        let resp = request()
+            .method(Method::HEAD)
+            .path("/info")
+            .send(&service)
+            .await;
+        assert_eq!(resp.status(), StatusCode::ACCEPTED);
+    }
+
+    #[tokio::test]
+    async fn test_url_for() {
+        use crate::{body::BoxBody, mount::mount, test::request, RequestExt};
+
+        let links = |req: Request<BoxBody>| async move {
+            let show = req.url_for("user.show", &[("id", "jørgen")])?;
+            let files = req.url_for("user.files", &[("id", "42"), ("path", "a/b c")])?;
+            Result::<_, crate::Error>::Ok(format!("{} {}", show, files))
+        };
+
+        let mut users = Router::<BoxBody>::new();
+        users
+            .get("/:id", links)
+            .unwrap()
+            .name("user.show")
+            .get("/:id/files/*path", links)
+            .unwrap()
+            .name("user.files");
+
+        let mut router = Router::<BoxBody>::new();
+        router.mount("/users", users).unwrap();
+        let service = mount("/api", router.into_service().unwrap());
+
+        let resp = request().path("/api/users/1").send(&service).await;
+        assert_eq!(
+            resp.text(),
+            "/api/users/j%C3%B8rgen /api/users/42/files/a/b%20c"
+        );
+
+        let req = request().build::<BoxBody>();
+        assert!(req.url_for("user.show", &[("id", "1")]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_url_for_fall_through() {
+        use crate::{body::BoxBody, test::request, RequestExt};
+
+        let mut inner = Router::<BoxBody>::new();
+        inner
+            .post(
+                "/items/:id",
+                |_req: Request<BoxBody>| async move { "posted" },
+            )
+            .unwrap()
+            .name("inner.item");
+
+        let mut router = Router::<BoxBody>::new();
+        router
+            .get("/items/:id", inner.into_service().unwrap())
+            .unwrap()
+            .get("/items/:id", |req: Request<BoxBody>| async move {
+                req.url_for("item", &[("id", "1")])
+            })
+            .unwrap()
+            .name("item");
+        let service = router.into_service().unwrap();
+
+        let resp = request().path("/items/1").send(&service).await;
+        assert_eq!(resp.text(), "/items/1");
+    }
+
+    #[test]
+    #[should_panic(expected = "already taken")]
+    fn test_duplicate_name() {
+        use crate::body::BoxBody;
+
+        let handler = |_req: Request<BoxBody>| async move { "ok" };
+
+        let mut router = Router::<BoxBody>::new();
+        router
+            .get("/a", handler)
+            .unwrap()
+            .name("page")
+            .get("/b", handler)
+            .unwrap()
+            .name("page");
+    }
+
+    #[test]
+    #[should_panic(expected = "already taken")]
+    fn test_duplicate_mounted_name() {
+        use crate::body::BoxBody;
+
+        let handler = |_req: Request<BoxBody>| async move { "ok" };
+
+        let mut users = Router::<BoxBody>::new();
+        users.get("/", handler).unwrap().name("index");
+
+        let mut router = Router::<BoxBody>::new();
+        router.get("/", handler).unwrap().name("index");
+        router.mount("/users", users).unwrap();
+        router.into_service().unwrap();
+    }
+
+    #[test]
+    fn test_routes() {
+        use crate::{body::BoxBody, catch_panic::CatchPanic};
+
+        let handler = |_req: Request<BoxBody>| async move { "ok" };
+
+        let mut router = Router::<BoxBody>::new();
+        router.get("/", handler).unwrap().name("home");
+
+        let mut router = router.wrap(CatchPanic);
+        router.post("/users/:id", handler).unwrap();
+
+        let routes = router
+            .routes()
+            .map(|route| {
+                (
+                    route.method().as_str(),
+                    route.path(),
+                    route.name(),
+                    route.middleware(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            routes,
+            [
+                ("GET", "/", Some("home"), 1),
+                ("POST", "/users/:id", None, 1)
+            ]
+        );
+
+        assert_eq!(
+            router.route_table().to_string(),
+            "GET   /           home  1\nPOST  /users/:id  -     1\n"
+        );
     }
 }
@@ -1,8 +1,7 @@
 use dale::{
-    combinators::shared::SharedService, BoxService, IntoOutcome, Middleware, MiddlewareFn, Service,
+    boxed::LocalBoxService, BoxService, IntoOutcome, Middleware, Service, ServiceExt,
     ServiceFailure, ServiceSuccess,
 };
-use futures_core::Future;
 use http::{Method, Request, Response};
 use router::AsSegments;
 
@@ -10,17 +9,77 @@ use crate::{Error, Reply};
 
 use super::{decorated::DecoratedRouter, Route};
 
+/// The service stored by a `Router`.
+pub type RouteService<B> = BoxService<'static, Request<B>, Response<B>, Error>;
+
+/// The service stored by a `LocalRouter`.
+pub type LocalRouteService<B> = LocalBoxService<'static, Request<B>, Response<B>, Error>;
+
+/// Services that can be boxed into the route service `T`.
+pub trait IntoRouteService<B, T> {
+    fn into_route_service(self) -> T;
+}
+
+impl<B, S> IntoRouteService<B, RouteService<B>> for S
+where
+    B: 'static,
+    S: Service<Request<B>> + Send + Sync + 'static,
+    S::Future: Send,
+    ServiceSuccess<Request<B>, S>: Reply<B> + Send,
+    ServiceFailure<Request<B>, S>: Into<Error>,
+{
+    fn into_route_service(self) -> RouteService<B> {
+        self.then(
+            |resp: <S::Output as IntoOutcome<Request<B>>>::Success| async move {
+                Result::<_, Error>::Ok(resp.into_response())
+            },
+        )
+        .err_into()
+        .boxed()
+    }
+}
+
+impl<B, S> IntoRouteService<B, LocalRouteService<B>> for S
+where
+    B: 'static,
+    S: Service<Request<B>> + 'static,
+    ServiceSuccess<Request<B>, S>: Reply<B>,
+    ServiceFailure<Request<B>, S>: Into<Error>,
+{
+    fn into_route_service(self) -> LocalRouteService<B> {
+        self.then(
+            |resp: <S::Output as IntoOutcome<Request<B>>>::Success| async move {
+                Result::<_, Error>::Ok(resp.into_response())
+            },
+        )
+        .err_into()
+        .boxed_local()
+    }
+}
+
+/// Middleware that can be applied to the route service `T`.
+pub trait RouteMiddleware<B, T> {
+    fn wrap_route(&self, service: T) -> T;
+}
+
+impl<B, T, M> RouteMiddleware<B, T> for M
+where
+    T: Service<Request<B>>,
+    M: Middleware<Request<B>, T>,
+    M::Service: IntoRouteService<B, T>,
+{
+    fn wrap_route(&self, service: T) -> T {
+        self.wrap(service).into_route_service()
+    }
+}
+
 macro_rules! impl_method {
     ($($name: ident => $method: ident),*) => {
         $(
             fn $name<'a, P, S>(&mut self, path: P, service: S) -> Result<&mut Self, P::Error>
             where
                 P: AsSegments<'a> + 'a,
-                B: 'static,
-                S: Service<Request<B>> + Send + Sync + 'static,
-                S::Future: Send,
-                ServiceSuccess<Request<B>, S>: Reply<B> + Send,
-                ServiceFailure<Request<B>, S>: Into<Error>,
+                S: IntoRouteService<B, Self::Service>,
             {
                 self.register(Method::$method, path, service)
             }
@@ -29,6 +88,8 @@ macro_rules! impl_method {
 }
 
 pub trait Routing<B> {
+    type Service: Service<Request<B>>;
+
     fn register<'a, P, S>(
         &mut self,
         method: Method,
@@ -37,30 +98,21 @@ pub trait Routing<B> {
     ) -> Result<&mut Self, P::Error>
     where
         P: AsSegments<'a> + 'a,
-        B: 'static,
-        S: Service<Request<B>> + Send + Sync + 'static,
-        S::Future: Send,
-        ServiceSuccess<Request<B>, S>: Reply<B> + Send,
-        ServiceFailure<Request<B>, S>: Into<Error>;
+        S: IntoRouteService<B, Self::Service>;
 
     fn mount<'a, 'b, P, I>(&mut self, path: P, router: I) -> Result<&mut Self, P::Error>
     where
         P: AsSegments<'a> + 'a,
-        I: IntoIterator<Item = router::Route<'b, Route<B>>>;
+        I: IntoIterator<Item = router::Route<'b, Route<B, Self::Service>>>;
 
     fn extend<'a, I>(&mut self, router: I) -> &mut Self
     where
-        I: IntoIterator<Item = router::Route<'a, Route<B>>>;
+        I: IntoIterator<Item = router::Route<'a, Route<B, Self::Service>>>;
 
-    fn wrap<M>(self, middleware: M) -> DecoratedRouter<B, M>
+    fn wrap<M>(self, middleware: M) -> DecoratedRouter<B, M, Self::Service>
     where
         Self: Sized,
-        B: Send + 'static,
-        M: Middleware<Request<B>, BoxService<'static, Request<B>, Response<B>, Error>> + Clone,
-        M::Service: Service<Request<B>> + Send + Sync + 'static,
-        <M::Service as Service<Request<B>>>::Future: Send,
-        ServiceSuccess<Request<B>, M::Service>: Reply<B> + Send,
-        ServiceFailure<Request<B>, M::Service>: Into<Error>;
+        M: RouteMiddleware<B, Self::Service> + Clone;
 
     impl_method!(
         get => GET,
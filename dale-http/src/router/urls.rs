@@ -0,0 +1,89 @@
+use super::route::RouteInfo;
+use crate::Error;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use std::{collections::HashMap, sync::Arc};
+
+const SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}')
+    .add(b'/');
+
+const WILDCARD: &AsciiSet = &SEGMENT.remove(b'/');
+
+/// Path templates of the named routes of a router. Inserted into the
+/// request extensions while dispatching, for [`RequestExt::url_for`].
+///
+/// [`RequestExt::url_for`]: crate::RequestExt::url_for
+#[derive(Debug, Default, Clone)]
+pub struct Urls {
+    i: Arc<HashMap<String, String>>,
+}
+
+impl Urls {
+    /// # Panics
+    ///
+    /// When two routes share a name, which [`Router::name`] can't catch
+    /// for names brought in by mounted routers.
+    ///
+    /// [`Router::name`]: super::Router::name
+    pub(super) fn new(routes: &[RouteInfo]) -> Urls {
+        let mut names = HashMap::new();
+        for route in routes {
+            if let Some(name) = &route.name {
+                let taken = names.insert(name.clone(), route.path.clone()).is_some();
+                assert!(!taken, "route name {} is already taken", name);
+            }
+        }
+
+        Urls { i: Arc::new(names) }
+    }
+
+    pub fn template(&self, name: &str) -> Option<&str> {
+        self.i.get(name).map(String::as_str)
+    }
+
+    /// The path of the route `name`, with its parameters filled in from
+    /// `params` and percent-encoded.
+    pub fn url_for(&self, name: &str, params: &[(&str, &str)]) -> Result<String, Error> {
+        let template = self
+            .template(name)
+            .ok_or_else(|| Error::new(format!("no route named {}", name)))?;
+
+        let lookup = |param: &str| {
+            params
+                .iter()
+                .find(|(key, _)| *key == param)
+                .map(|(_, value)| *value)
+                .ok_or_else(|| {
+                    Error::new(format!("missing parameter {} for route {}", param, name))
+                })
+        };
+
+        let mut url = String::with_capacity(template.len());
+        for segment in template.split('/').filter(|segment| !segment.is_empty()) {
+            url.push('/');
+            if let Some(param) = segment.strip_prefix(':') {
+                url.extend(utf8_percent_encode(lookup(param)?, SEGMENT));
+            } else if let Some(param) = segment.strip_prefix('*') {
+                let value = lookup(param)?;
+                url.extend(utf8_percent_encode(value.trim_start_matches('/'), WILDCARD));
+            } else {
+                url.push_str(segment);
+            }
+        }
+
+        if url.is_empty() {
+            url.push('/');
+        }
+
+        Ok(url)
+    }
+}
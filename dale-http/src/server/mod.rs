@@ -0,0 +1,424 @@
+//! Run a service on a socket, with graceful shutdown.
+//!
+//! The peer address of every TCP connection is inserted into the request
+//! extensions as a [`SocketAddr`]. Connections on a Unix socket get the
+//! peer's [`PeerCred`](unix::PeerCred) instead.
+use crate::{hyper::MakeTaskHyperService, Error, Reply};
+use dale::{IntoOutcome, Service};
+use hyper::{
+    server::conn::AddrStream, service::make_service_fn, Body, Request, Response,
+    Server as HyperServer,
+};
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    future::{pending, Future},
+    net::{SocketAddr, TcpListener},
+    path::PathBuf,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{sync::oneshot, task::AbortHandle};
+
+#[cfg(feature = "tls")]
+pub mod tls;
+#[cfg(unix)]
+pub mod unix;
+
+type Shutdown = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+enum Bind {
+    Addr(SocketAddr),
+    Listener(TcpListener),
+    #[cfg(unix)]
+    Unix {
+        path: PathBuf,
+        mode: Option<u32>,
+    },
+}
+
+impl Bind {
+    #[cfg(feature = "tls")]
+    fn into_listener(self) -> std::io::Result<TcpListener> {
+        match self {
+            Bind::Addr(addr) => TcpListener::bind(addr),
+            Bind::Listener(listener) => Ok(listener),
+            #[cfg(unix)]
+            Bind::Unix { .. } => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "tls is only served over tcp",
+            )),
+        }
+    }
+}
+
+pub fn serve<T>(addr: impl Into<SocketAddr>, service: T) -> Server<T>
+where
+    T: Service<Request<Body>>,
+{
+    Server::new(Bind::Addr(addr.into()), service)
+}
+
+/// Serve on a Unix domain socket at `path`.
+#[cfg(unix)]
+pub fn serve_unix<T>(path: impl Into<PathBuf>, service: T) -> Server<T>
+where
+    T: Service<Request<Body>>,
+{
+    Server::new(
+        Bind::Unix {
+            path: path.into(),
+            mode: None,
+        },
+        service,
+    )
+}
+
+pub struct Server<T> {
+    bind: Bind,
+    make: MakeTaskHyperService<T>,
+    shutdown: Option<Shutdown>,
+    drain_timeout: Option<Duration>,
+    #[cfg(feature = "tls")]
+    tls: Option<tls::TlsConfig>,
+}
+
+impl<T> Server<T> {
+    fn new(bind: Bind, service: T) -> Server<T> {
+        Server {
+            bind,
+            make: MakeTaskHyperService::new(service),
+            shutdown: None,
+            drain_timeout: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+
+    /// Serve on an already bound listener.
+    pub fn from_listener(listener: TcpListener, service: T) -> Server<T> {
+        Server::new(Bind::Listener(listener), service)
+    }
+
+    /// Permissions of the Unix socket file, like `0o660`. Ignored for TCP.
+    #[cfg(unix)]
+    pub fn mode(mut self, mode: u32) -> Self {
+        if let Bind::Unix {
+            mode: bind_mode, ..
+        } = &mut self.bind
+        {
+            *bind_mode = Some(mode);
+        }
+        self
+    }
+
+    /// Replace the response for requests passed on with `Next`. Defaults to
+    /// [`not_found`](crate::hyper::not_found).
+    pub fn fallback<F>(mut self, fallback: F) -> Self
+    where
+        F: Fn(Request<Body>) -> Response<Body> + Send + Sync + 'static,
+    {
+        self.make = self.make.fallback(fallback);
+        self
+    }
+
+    /// Stop accepting connections once `signal` completes, and wait for the
+    /// open ones to finish.
+    pub fn with_graceful_shutdown<F>(mut self, signal: F) -> Self
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.shutdown = Some(Box::pin(signal));
+        self
+    }
+
+    /// Shut down gracefully on ctrl-c, or `SIGTERM` on unix.
+    pub fn with_signal(self) -> Self {
+        self.with_graceful_shutdown(shutdown_signal())
+    }
+
+    /// How long open connections may keep running after shutdown started.
+    /// Connections still open then are closed, aborting the requests in
+    /// flight. Without it, shutdown waits for all of them.
+    pub fn drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = Some(timeout);
+        self
+    }
+
+    /// Terminate TLS on every connection.
+    #[cfg(feature = "tls")]
+    pub fn tls(mut self, config: tls::TlsConfig) -> Self {
+        self.tls = Some(config);
+        self
+    }
+
+    pub async fn run(self) -> Result<(), Error>
+    where
+        T: Service<Request<Body>> + Clone + Send + Sync + 'static,
+        T::Future: Send,
+        <T::Output as IntoOutcome<Request<Body>>>::Success: Reply<Body>,
+        <T::Output as IntoOutcome<Request<Body>>>::Failure: Into<Error>,
+    {
+        let (signal, drain) = graceful(self.shutdown);
+        let connections = Connections::default();
+        let make = self.make;
+
+        #[cfg(feature = "tls")]
+        if let Some(config) = self.tls {
+            config.reload()?;
+            let incoming = tls::TlsIncoming::new(self.bind.into_listener()?, &config)?;
+            let make = make_service_fn(move |conn: &tls::TlsStream| {
+                let service = make.service(conn.get_ref().0.peer_addr().ok());
+                async move { Ok::<_, Infallible>(service) }
+            });
+
+            let watch = config.watch();
+            let server = HyperServer::builder(incoming)
+                .executor(connections.clone())
+                .serve(make)
+                .with_graceful_shutdown(signal);
+            let ret = run_until_drained(server, drain, self.drain_timeout, connections).await;
+            if let Some(watch) = watch {
+                watch.abort();
+            }
+            return ret;
+        }
+
+        let builder = match self.bind {
+            Bind::Addr(addr) => HyperServer::try_bind(&addr)?,
+            Bind::Listener(listener) => HyperServer::from_tcp(listener)?,
+            #[cfg(unix)]
+            Bind::Unix { path, mode } => {
+                let incoming = unix::UnixIncoming::bind(&path, mode)?;
+                let make = make_service_fn(move |conn: &tokio::net::UnixStream| {
+                    let service = match unix::PeerCred::of(conn) {
+                        Some(cred) => make.service_with(cred),
+                        None => make.service(None),
+                    };
+                    async move { Ok::<_, Infallible>(service) }
+                });
+
+                let server = HyperServer::builder(incoming)
+                    .executor(connections.clone())
+                    .serve(make)
+                    .with_graceful_shutdown(signal);
+                let ret = run_until_drained(server, drain, self.drain_timeout, connections).await;
+                std::fs::remove_file(&path).ok();
+                return ret;
+            }
+        };
+
+        let make = make_service_fn(move |conn: &AddrStream| {
+            let service = make.service(Some(conn.remote_addr()));
+            async move { Ok::<_, Infallible>(service) }
+        });
+
+        let server = builder
+            .executor(connections.clone())
+            .serve(make)
+            .with_graceful_shutdown(signal);
+        run_until_drained(server, drain, self.drain_timeout, connections).await
+    }
+}
+
+/// The signal handed to hyper, and a receiver completing once it fired.
+fn graceful(shutdown: Option<Shutdown>) -> (impl Future<Output = ()>, oneshot::Receiver<()>) {
+    let (started, drain) = oneshot::channel();
+    let signal = async move {
+        match shutdown {
+            Some(shutdown) => shutdown.await,
+            None => pending().await,
+        }
+        started.send(()).ok();
+    };
+    (signal, drain)
+}
+
+/// Spawns the tasks hyper serves connections on, keeping a handle to each
+/// so the ones still running after the drain timeout can be aborted.
+#[derive(Clone, Default)]
+struct Connections {
+    tasks: Arc<Mutex<Tasks>>,
+}
+
+#[derive(Default)]
+struct Tasks {
+    next: u64,
+    running: HashMap<u64, AbortHandle>,
+}
+
+impl Connections {
+    /// Aborts every running task, returning how many there were.
+    fn abort(&self) -> usize {
+        let running = std::mem::take(&mut self.tasks.lock().unwrap().running);
+        for task in running.values() {
+            task.abort();
+        }
+        running.len()
+    }
+}
+
+impl<F> hyper::rt::Executor<F> for Connections
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    fn execute(&self, future: F) {
+        // Held while spawning, so the task can't remove itself before it
+        // was added.
+        let mut tasks = self.tasks.lock().unwrap();
+        let id = tasks.next;
+        tasks.next += 1;
+
+        let shared = self.tasks.clone();
+        let task = tokio::spawn(async move {
+            future.await;
+            shared.lock().unwrap().running.remove(&id);
+        });
+        tasks.running.insert(id, task.abort_handle());
+    }
+}
+
+async fn run_until_drained<F>(
+    server: F,
+    drain: oneshot::Receiver<()>,
+    timeout: Option<Duration>,
+    connections: Connections,
+) -> Result<(), Error>
+where
+    F: Future<Output = Result<(), hyper::Error>>,
+{
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return server.await.map_err(Error::from),
+    };
+
+    let deadline = async move {
+        match drain.await {
+            Ok(()) => tokio::time::sleep(timeout).await,
+            Err(_) => pending().await,
+        }
+    };
+
+    tokio::select! {
+        ret = server => ret.map_err(Error::from),
+        _ = deadline => {
+            let open = connections.abort();
+            tracing::warn!("closed {} connections still open after {:?}", open, timeout);
+            Ok(())
+        }
+    }
+}
+
+/// Completes on ctrl-c, or `SIGTERM` on unix.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c().await.ok();
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => pending().await,
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::StatusCode;
+    use std::io::{Read, Write};
+
+    fn get(addr: SocketAddr, path: &str) -> String {
+        let mut stream = std::net::TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+            path
+        )
+        .unwrap();
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).unwrap();
+        resp
+    }
+
+    #[tokio::test]
+    async fn test_serve() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let service = |req: Request<Body>| async move {
+            if req.uri().path() != "/" {
+                return crate::Outcome::Next(req);
+            }
+            let peer = req.extensions().get::<SocketAddr>().unwrap();
+            crate::Outcome::Success(Response::new(Body::from(peer.ip().to_string())))
+        };
+
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = Server::from_listener(listener, service)
+            .fallback(|_| {
+                let mut resp = Response::new(Body::empty());
+                *resp.status_mut() = StatusCode::IM_A_TEAPOT;
+                resp
+            })
+            .with_graceful_shutdown(async move {
+                stopped.await.ok();
+            })
+            .drain_timeout(Duration::from_secs(1));
+        let server = tokio::spawn(server.run());
+
+        let resp = tokio::task::spawn_blocking(move || (get(addr, "/"), get(addr, "/missing")))
+            .await
+            .unwrap();
+        assert!(resp.0.starts_with("HTTP/1.1 200 OK"));
+        assert!(resp.0.ends_with("127.0.0.1"));
+        assert!(resp.1.starts_with("HTTP/1.1 418"));
+
+        stop.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_drain_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let (started, handling) = tokio::sync::oneshot::channel::<()>();
+        let started = Arc::new(Mutex::new(Some(started)));
+        let service = move |_req: Request<Body>| {
+            if let Some(started) = started.lock().unwrap().take() {
+                started.send(()).ok();
+            }
+            pending::<crate::Outcome<Body>>()
+        };
+
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = Server::from_listener(listener, service)
+            .with_graceful_shutdown(async move {
+                stopped.await.ok();
+            })
+            .drain_timeout(Duration::from_millis(100));
+        let server = tokio::spawn(server.run());
+
+        let client = tokio::task::spawn_blocking(move || get(addr, "/"));
+        handling.await.unwrap();
+        stop.send(()).unwrap();
+
+        server.await.unwrap().unwrap();
+        assert_eq!(client.await.unwrap(), "");
+    }
+}
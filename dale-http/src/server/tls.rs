@@ -0,0 +1,347 @@
+//! TLS termination with rustls.
+//!
+//! Certificates are read from PEM files. Additional certificates are picked
+//! by the SNI name the client asks for, falling back to the default one.
+//! Every certificate can be reloaded from disk while the server runs, on
+//! demand with [`TlsConfig::reload`] or by polling the files with
+//! [`TlsConfig::reload_every`].
+use crate::Error;
+use hyper::server::accept::Accept;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufReader},
+    net::TcpListener,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{Arc, Mutex, RwLock},
+    task::{Context, Poll},
+    time::{Duration, SystemTime},
+};
+use tokio::{net::TcpStream, sync::mpsc, task::JoinHandle};
+use tokio_rustls::{
+    rustls::{
+        crypto::ring,
+        server::{ClientHello, ResolvesServerCert},
+        sign::CertifiedKey,
+        ServerConfig,
+    },
+    TlsAcceptor,
+};
+
+pub type TlsStream = tokio_rustls::server::TlsStream<TcpStream>;
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Certificates to serve. Clones share the loaded certificates, so a clone
+/// kept aside can reload them for a running server.
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    resolver: Arc<Resolver>,
+    reload_interval: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    name: Option<String>,
+    cert: PathBuf,
+    key: PathBuf,
+}
+
+impl TlsConfig {
+    /// The default certificate chain and private key.
+    pub fn new(cert: impl Into<PathBuf>, key: impl Into<PathBuf>) -> TlsConfig {
+        let config = TlsConfig {
+            resolver: Arc::default(),
+            reload_interval: None,
+        };
+        config.push(None, cert.into(), key.into());
+        config
+    }
+
+    /// A certificate for clients asking for `name`.
+    pub fn sni(
+        self,
+        name: impl Into<String>,
+        cert: impl Into<PathBuf>,
+        key: impl Into<PathBuf>,
+    ) -> Self {
+        let name = name.into().to_ascii_lowercase();
+        self.push(Some(name), cert.into(), key.into());
+        self
+    }
+
+    /// Check the files for changes every `interval`, and reload them when
+    /// they do. Failed reloads keep the previous certificates.
+    pub fn reload_every(mut self, interval: Duration) -> Self {
+        self.reload_interval = Some(interval);
+        self
+    }
+
+    /// Read every certificate from disk again.
+    pub fn reload(&self) -> Result<(), Error> {
+        let entries = self.resolver.entries.lock().unwrap().clone();
+
+        let mut certs = Certs::default();
+        for entry in entries {
+            let key = load(&entry.cert, &entry.key)?;
+            match entry.name {
+                Some(name) => {
+                    certs.names.insert(name, key);
+                }
+                None => certs.default = Some(key),
+            }
+        }
+
+        *self.resolver.certs.write().unwrap() = certs;
+        Ok(())
+    }
+
+    fn push(&self, name: Option<String>, cert: PathBuf, key: PathBuf) {
+        let mut entries = self.resolver.entries.lock().unwrap();
+        entries.retain(|entry| entry.name != name);
+        entries.push(Entry { name, cert, key });
+    }
+
+    fn server_config(&self) -> Result<Arc<ServerConfig>, Error> {
+        let mut config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(Error::new)?
+            .with_no_client_auth()
+            .with_cert_resolver(self.resolver.clone());
+
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Ok(Arc::new(config))
+    }
+
+    fn modified(&self) -> Vec<Option<SystemTime>> {
+        let entries = self.resolver.entries.lock().unwrap();
+        entries
+            .iter()
+            .flat_map(|entry| [&entry.cert, &entry.key])
+            .map(|path| path.metadata().and_then(|meta| meta.modified()).ok())
+            .collect()
+    }
+
+    /// Poll the files in the background, if configured to.
+    pub(crate) fn watch(&self) -> Option<JoinHandle<()>> {
+        let interval = self.reload_interval?;
+        let config = self.clone();
+
+        Some(tokio::spawn(async move {
+            let mut seen = config.modified();
+            loop {
+                tokio::time::sleep(interval).await;
+                let modified = config.modified();
+                if modified == seen {
+                    continue;
+                }
+
+                match config.reload() {
+                    Ok(()) => {
+                        tracing::info!("reloaded tls certificates");
+                        seen = modified;
+                    }
+                    Err(err) => tracing::warn!("could not reload tls certificates: {}", err),
+                }
+            }
+        }))
+    }
+}
+
+#[derive(Debug, Default)]
+struct Certs {
+    default: Option<Arc<CertifiedKey>>,
+    names: HashMap<String, Arc<CertifiedKey>>,
+}
+
+#[derive(Debug, Default)]
+struct Resolver {
+    entries: Mutex<Vec<Entry>>,
+    certs: RwLock<Certs>,
+}
+
+impl ResolvesServerCert for Resolver {
+    fn resolve(&self, hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        let certs = self.certs.read().unwrap();
+        hello
+            .server_name()
+            .and_then(|name| certs.names.get(&name.to_ascii_lowercase()))
+            .or(certs.default.as_ref())
+            .cloned()
+    }
+}
+
+fn load(cert: &Path, key: &Path) -> Result<Arc<CertifiedKey>, Error> {
+    let context = |path: &Path, err: &dyn std::fmt::Display| {
+        Error::new(format!("{}: {}", path.display(), err))
+    };
+
+    let chain = rustls_pemfile::certs(&mut BufReader::new(
+        File::open(cert).map_err(|err| context(cert, &err))?,
+    ))
+    .collect::<Result<Vec<_>, _>>()
+    .map_err(|err| context(cert, &err))?;
+
+    if chain.is_empty() {
+        return Err(context(cert, &"no certificates found"));
+    }
+
+    let private_key = rustls_pemfile::private_key(&mut BufReader::new(
+        File::open(key).map_err(|err| context(key, &err))?,
+    ))
+    .map_err(|err| context(key, &err))?
+    .ok_or_else(|| context(key, &"no private key found"))?;
+
+    let signing_key =
+        ring::sign::any_supported_type(&private_key).map_err(|err| context(key, &err))?;
+
+    Ok(Arc::new(CertifiedKey::new(chain, signing_key)))
+}
+
+/// Accepts TCP connections and completes their handshakes in the background,
+/// so a slow client doesn't hold up the others.
+pub(crate) struct TlsIncoming {
+    rx: mpsc::Receiver<TlsStream>,
+}
+
+impl TlsIncoming {
+    pub(crate) fn new(listener: TcpListener, config: &TlsConfig) -> Result<TlsIncoming, Error> {
+        listener.set_nonblocking(true)?;
+        let listener = tokio::net::TcpListener::from_std(listener)?;
+        let acceptor = TlsAcceptor::from(config.server_config()?);
+        let (tx, rx) = mpsc::channel(64);
+
+        tokio::spawn(async move {
+            loop {
+                let stream = tokio::select! {
+                    _ = tx.closed() => break,
+                    ret = listener.accept() => match ret {
+                        Ok((stream, _)) => stream,
+                        Err(err) => {
+                            tracing::warn!("accept failed: {}", err);
+                            tokio::time::sleep(Duration::from_millis(100)).await;
+                            continue;
+                        }
+                    },
+                };
+
+                let acceptor = acceptor.clone();
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    match tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                        Ok(Ok(stream)) => {
+                            tx.send(stream).await.ok();
+                        }
+                        Ok(Err(err)) => tracing::debug!("tls handshake failed: {}", err),
+                        Err(_) => tracing::debug!("tls handshake timed out"),
+                    }
+                });
+            }
+        });
+
+        Ok(TlsIncoming { rx })
+    }
+}
+
+impl Accept for TlsIncoming {
+    type Conn = TlsStream;
+    type Error = io::Error;
+
+    fn poll_accept(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        self.rx.poll_recv(cx).map(|conn| conn.map(Ok))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::Server;
+    use hyper::{Body, Request, Response};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio_rustls::{
+        rustls::{pki_types::ServerName, ClientConfig, RootCertStore},
+        TlsConnector,
+    };
+
+    fn generate(dir: &Path, file: &str, name: &str) -> rcgen::CertifiedKey {
+        let cert = rcgen::generate_simple_self_signed(vec![name.to_string()]).unwrap();
+        std::fs::write(dir.join(format!("{}.crt", file)), cert.cert.pem()).unwrap();
+        std::fs::write(
+            dir.join(format!("{}.key", file)),
+            cert.key_pair.serialize_pem(),
+        )
+        .unwrap();
+        cert
+    }
+
+    async fn get(addr: std::net::SocketAddr, name: &str, trust: &rcgen::CertifiedKey) -> String {
+        let mut roots = RootCertStore::empty();
+        roots.add(trust.cert.der().clone()).unwrap();
+        let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut stream = TlsConnector::from(Arc::new(config))
+            .connect(ServerName::try_from(name.to_string()).unwrap(), stream)
+            .await
+            .unwrap();
+
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut resp = String::new();
+        stream.read_to_string(&mut resp).await.ok();
+        resp
+    }
+
+    #[tokio::test]
+    async fn test_tls() {
+        let dir = std::env::temp_dir().join(format!("dale-tls-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let localhost = generate(&dir, "default", "localhost");
+        let example = generate(&dir, "example", "example.test");
+        let config = TlsConfig::new(dir.join("default.crt"), dir.join("default.key")).sni(
+            "Example.test",
+            dir.join("example.crt"),
+            dir.join("example.key"),
+        );
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let service = |_req: Request<Body>| async move {
+            crate::Outcome::Success(Response::new(Body::from("secure")))
+        };
+
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = Server::from_listener(listener, service)
+            .tls(config.clone())
+            .with_graceful_shutdown(async move {
+                stopped.await.ok();
+            });
+        let server = tokio::spawn(server.run());
+
+        let resp = get(addr, "localhost", &localhost).await;
+        assert!(resp.starts_with("HTTP/1.1 200 OK"));
+        assert!(resp.ends_with("secure"));
+        assert!(get(addr, "example.test", &example)
+            .await
+            .ends_with("secure"));
+
+        let renewed = generate(&dir, "default", "localhost");
+        config.reload().unwrap();
+        assert!(get(addr, "localhost", &renewed).await.ends_with("secure"));
+
+        stop.send(()).unwrap();
+        server.await.unwrap().unwrap();
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
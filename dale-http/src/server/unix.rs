@@ -0,0 +1,219 @@
+//! Serving on a Unix domain socket.
+//!
+//! A socket file left behind by a server that is no longer running is
+//! removed before binding, and the file is removed again on shutdown. The
+//! credentials of the connecting process are inserted into the request
+//! extensions as a [`PeerCred`].
+use futures_core::ready;
+use http::Request;
+use hyper::server::accept::Accept;
+use std::{
+    fs::{self, Permissions},
+    future::Future,
+    io,
+    os::unix::{
+        fs::{DirBuilderExt, FileTypeExt, PermissionsExt},
+        net::UnixStream as StdUnixStream,
+    },
+    path::Path,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    net::{UnixListener, UnixStream},
+    time::Sleep,
+};
+
+/// The process on the other end of a Unix socket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeerCred {
+    pub uid: u32,
+    pub gid: u32,
+    /// Not reported on every platform.
+    pub pid: Option<i32>,
+}
+
+impl PeerCred {
+    pub(crate) fn of(stream: &UnixStream) -> Option<PeerCred> {
+        let cred = stream.peer_cred().ok()?;
+        Some(PeerCred {
+            uid: cred.uid(),
+            gid: cred.gid(),
+            pid: cred.pid(),
+        })
+    }
+}
+
+/// The credentials of the peer, as inserted by the server.
+pub fn peer_cred<B>(req: &Request<B>) -> Option<PeerCred> {
+    req.extensions().get::<PeerCred>().copied()
+}
+
+pub(crate) struct UnixIncoming {
+    listener: UnixListener,
+    backoff: Option<Pin<Box<Sleep>>>,
+}
+
+impl UnixIncoming {
+    /// Bind to `path`, replacing a stale socket, and apply `mode` to it.
+    ///
+    /// With a `mode`, the socket is bound inside a private directory next to
+    /// `path` and moved into place once its permissions are set, so it is
+    /// never reachable with the default ones.
+    pub(crate) fn bind(path: &Path, mode: Option<u32>) -> io::Result<UnixIncoming> {
+        remove_stale(path)?;
+        let listener = match mode {
+            Some(mode) => bind_with_mode(path, mode)?,
+            None => UnixListener::bind(path)?,
+        };
+        Ok(UnixIncoming {
+            listener,
+            backoff: None,
+        })
+    }
+}
+
+fn bind_with_mode(path: &Path, mode: u32) -> io::Result<UnixListener> {
+    let name = path.file_name().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a file path", path.display()),
+        )
+    })?;
+    let dir = path.with_file_name(format!(
+        ".{}.{}",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+    fs::DirBuilder::new().mode(0o700).create(&dir)?;
+
+    let socket = dir.join("socket");
+    let ret = UnixListener::bind(&socket).and_then(|listener| {
+        fs::set_permissions(&socket, Permissions::from_mode(mode))?;
+        fs::rename(&socket, path)?;
+        Ok(listener)
+    });
+
+    fs::remove_file(&socket).ok();
+    fs::remove_dir(&dir)?;
+    ret
+}
+
+impl Accept for UnixIncoming {
+    type Conn = UnixStream;
+    type Error = io::Error;
+
+    /// Failed accepts, like running out of file descriptors, are logged and
+    /// retried after a short pause instead of stopping the server.
+    fn poll_accept(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        loop {
+            if let Some(backoff) = &mut self.backoff {
+                ready!(backoff.as_mut().poll(cx));
+                self.backoff = None;
+            }
+
+            match ready!(self.listener.poll_accept(cx)) {
+                Ok((stream, _)) => return Poll::Ready(Some(Ok(stream))),
+                Err(err) => {
+                    tracing::warn!("accept failed: {}", err);
+                    self.backoff = Some(Box::pin(tokio::time::sleep(Duration::from_millis(100))));
+                }
+            }
+        }
+    }
+}
+
+/// Remove the socket at `path` if nothing is listening on it. Anything that
+/// isn't a socket is left alone.
+fn remove_stale(path: &Path) -> io::Result<()> {
+    let meta = match fs::symlink_metadata(path) {
+        Ok(meta) => meta,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+
+    if !meta.file_type().is_socket() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists and is not a socket", path.display()),
+        ));
+    }
+
+    match StdUnixStream::connect(path) {
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("{} is in use", path.display()),
+        )),
+        Err(err) if err.kind() == io::ErrorKind::ConnectionRefused => fs::remove_file(path),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::serve_unix;
+    use hyper::{Body, Response};
+    use std::{
+        io::{Read, Write},
+        os::unix::{fs::MetadataExt, net::UnixListener as StdUnixListener},
+    };
+
+    #[tokio::test]
+    async fn test_unix() {
+        let path = std::env::temp_dir().join(format!("dale-{}.sock", std::process::id()));
+        drop(StdUnixListener::bind(&path).unwrap());
+        assert!(path.exists());
+
+        let service = |req: Request<Body>| async move {
+            let cred = peer_cred(&req).unwrap();
+            crate::Outcome::Success(Response::new(Body::from(cred.uid.to_string())))
+        };
+
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = serve_unix(&path, service)
+            .mode(0o600)
+            .with_graceful_shutdown(async move {
+                stopped.await.ok();
+            });
+        let server = tokio::spawn(server.run());
+
+        let socket = path.clone();
+        let resp = tokio::task::spawn_blocking(move || {
+            let mut stream = loop {
+                match StdUnixStream::connect(&socket) {
+                    Ok(stream) => break stream,
+                    Err(_) => std::thread::sleep(std::time::Duration::from_millis(10)),
+                }
+            };
+            stream
+                .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                .unwrap();
+            let mut resp = String::new();
+            stream.read_to_string(&mut resp).unwrap();
+            resp
+        })
+        .await
+        .unwrap();
+
+        let meta = fs::metadata(&path).unwrap();
+        assert_eq!(meta.mode() & 0o777, 0o600);
+        assert!(!path
+            .with_file_name(format!(
+                ".{}.{}",
+                path.file_name().unwrap().to_string_lossy(),
+                std::process::id()
+            ))
+            .exists());
+        assert!(resp.starts_with("HTTP/1.1 200 OK"));
+        assert!(resp.ends_with(&meta.uid().to_string()));
+
+        stop.send(()).unwrap();
+        server.await.unwrap().unwrap();
+        assert!(!path.exists());
+    }
+}
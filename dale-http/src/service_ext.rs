@@ -1,5 +1,5 @@
 use core::marker::PhantomData;
-use dale::{IntoOutcome, Service, ServiceFailure, ServiceSuccess};
+use dale::{Describe, Description, IntoOutcome, Service, ServiceFailure, ServiceSuccess};
 use futures_core::{ready, Future};
 use http::Request;
 
@@ -35,6 +35,15 @@ where
     }
 }
 
+impl<S> Describe for IntoResponseService<S>
+where
+    S: Describe,
+{
+    fn describe(&self) -> Description {
+        self.0.describe()
+    }
+}
+
 pin_project_lite::pin_project! {
     pub struct IntoResponseFuture<S, B> where S: Service<Request<B>> {
         #[pin]
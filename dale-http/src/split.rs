@@ -0,0 +1,46 @@
+//! Sticky key extractors for [`dale::split`].
+//!
+//! ```ignore
+//! let service = dale::split(vec![(90, stable), (10, canary)])
+//!     .sticky(dale_http::split::cookie_key("session"));
+//! ```
+use http::{header::AsHeaderName, HeaderValue, Request};
+
+/// Keep requests carrying the same value for header `name` on the same service.
+pub fn header_key<B, S>(name: S) -> impl Fn(&Request<B>) -> Option<HeaderValue> + Clone
+where
+    S: AsHeaderName + Clone,
+{
+    move |req: &Request<B>| req.headers().get(name.clone()).cloned()
+}
+
+/// Keep requests carrying the same value for cookie `name` on the same service.
+pub fn cookie_key<B>(name: impl Into<String>) -> impl Fn(&Request<B>) -> Option<String> + Clone {
+    let name = name.into();
+    move |req: &Request<B>| {
+        req.headers()
+            .get_all(http::header::COOKIE)
+            .iter()
+            .filter_map(|header| header.to_str().ok())
+            .flat_map(|header| header.split(';'))
+            .filter_map(|pair| pair.trim().split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.trim_matches('"').to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cookie_key() {
+        let req = Request::builder()
+            .header("cookie", "theme=dark; session=\"abc\"")
+            .body(())
+            .unwrap();
+
+        assert_eq!(cookie_key("session")(&req), Some("abc".to_owned()));
+        assert_eq!(cookie_key("user")(&req), None);
+    }
+}
@@ -0,0 +1,275 @@
+//! Call services in memory, without binding a server.
+//!
+//! Build a request with [`request`], send it to any `Service<Request<B>>`
+//! and inspect the buffered [`TestResponse`]. Outcomes are answered like the
+//! hyper adapter does: a failure is rendered from its [`Error`], and a
+//! request passed on with `Next` gets the preferred rejection status, or
+//! `404 Not Found`. Rejection tracking is on for every request sent.
+use crate::{
+    common::SharedResponse,
+    error::{BoxError, Error},
+    rejection::{self, Rejections},
+    Body, Reply,
+};
+use bytes::Bytes;
+use dale::{IntoOutcome, Outcome, Service};
+use http::{
+    header::{self, HeaderName},
+    Extensions, HeaderMap, HeaderValue, Method, Request, StatusCode,
+};
+use std::convert::TryFrom;
+
+pub fn request() -> RequestBuilder {
+    RequestBuilder::default()
+}
+
+#[derive(Debug)]
+pub struct RequestBuilder {
+    method: Method,
+    path: String,
+    headers: HeaderMap,
+    extensions: Extensions,
+    body: Vec<u8>,
+}
+
+impl Default for RequestBuilder {
+    fn default() -> Self {
+        RequestBuilder {
+            method: Method::GET,
+            path: "/".to_string(),
+            headers: HeaderMap::new(),
+            extensions: Extensions::new(),
+            body: Vec::new(),
+        }
+    }
+}
+
+impl RequestBuilder {
+    pub fn method(mut self, method: Method) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// The request path, including an optional query.
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Append a header. Panics if the name or value is invalid.
+    pub fn header<K, V>(mut self, name: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        let name = HeaderName::try_from(name)
+            .map_err(Into::into)
+            .expect("invalid header name");
+        let value = HeaderValue::try_from(value)
+            .map_err(Into::into)
+            .expect("invalid header value");
+        self.headers.append(name, value);
+        self
+    }
+
+    pub fn cookie(mut self, name: &str, value: &str) -> Self {
+        let cookie = match self.headers.remove(header::COOKIE) {
+            Some(prev) => format!("{}; {}={}", prev.to_str().unwrap_or_default(), name, value),
+            None => format!("{}={}", name, value),
+        };
+        self.headers.insert(
+            header::COOKIE,
+            HeaderValue::try_from(cookie).expect("invalid cookie"),
+        );
+        self
+    }
+
+    pub fn extension<T: Send + Sync + 'static>(mut self, extension: T) -> Self {
+        self.extensions.insert(extension);
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    pub fn text(self, text: impl Into<String>) -> Self {
+        self.content_type("text/plain; charset=utf-8")
+            .body(text.into())
+    }
+
+    #[cfg(feature = "json")]
+    pub fn json<T: serde::Serialize>(self, value: &T) -> Self {
+        let body = serde_json::to_vec(value).expect("serialize json");
+        self.content_type("application/json").body(body)
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn form<T: serde::Serialize>(self, value: &T) -> Self {
+        let body = serde_urlencoded::to_string(value).expect("serialize form");
+        self.content_type("application/x-www-form-urlencoded")
+            .body(body)
+    }
+
+    fn content_type(mut self, content_type: &'static str) -> Self {
+        if !self.headers.contains_key(header::CONTENT_TYPE) {
+            self.headers
+                .insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+        }
+        self
+    }
+
+    pub fn build<B: Body>(self) -> Request<B> {
+        let mut req = Request::new(B::from_bytes(self.body));
+        *req.method_mut() = self.method;
+        *req.uri_mut() = self.path.parse().expect("invalid path");
+        *req.headers_mut() = self.headers;
+        *req.extensions_mut() = self.extensions;
+        req
+    }
+
+    /// Send the request to `service`.
+    pub async fn send<S, B>(self, service: &S) -> TestResponse
+    where
+        S: Service<Request<B>>,
+        B: Body,
+        B::Error: Into<BoxError>,
+        <S::Output as IntoOutcome<Request<B>>>::Success: Reply<B>,
+        <S::Output as IntoOutcome<Request<B>>>::Failure: Into<Error>,
+    {
+        let resp = match self.try_send(service).await {
+            Ok(resp) => return resp,
+            Err(err) => Reply::<B>::into_response(err),
+        };
+
+        SharedResponse::buffer(resp)
+            .await
+            .map(TestResponse)
+            .expect("buffer error response")
+    }
+
+    /// Send the request to `service`, returning its failure as is.
+    pub async fn try_send<S, B>(self, service: &S) -> Result<TestResponse, Error>
+    where
+        S: Service<Request<B>>,
+        B: Body,
+        B::Error: Into<BoxError>,
+        <S::Output as IntoOutcome<Request<B>>>::Success: Reply<B>,
+        <S::Output as IntoOutcome<Request<B>>>::Failure: Into<Error>,
+    {
+        let mut req = self.build();
+        if req.extensions().get::<Rejections>().is_none() {
+            req.extensions_mut().insert(Rejections::default());
+        }
+
+        let resp = match service.call(req).await.into_outcome() {
+            Outcome::Success(reply) => reply.into_response(),
+            Outcome::Failure(err) => return Err(err.into()),
+            Outcome::Next(req) => rejection::respond(&req),
+        };
+
+        SharedResponse::buffer(resp).await.map(TestResponse)
+    }
+}
+
+/// A response with its body buffered.
+#[derive(Debug, Clone)]
+pub struct TestResponse(SharedResponse);
+
+impl TestResponse {
+    pub fn status(&self) -> StatusCode {
+        self.0.status()
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        self.0.headers()
+    }
+
+    pub fn header(&self, name: impl header::AsHeaderName) -> Option<&str> {
+        self.0.headers().get(name).and_then(|v| v.to_str().ok())
+    }
+
+    pub fn bytes(&self) -> &Bytes {
+        self.0.body()
+    }
+
+    /// The body as text. Panics if it isn't valid UTF-8.
+    pub fn text(&self) -> &str {
+        std::str::from_utf8(self.0.body()).expect("body is not utf-8")
+    }
+
+    /// Deserialize the body. Panics if it isn't valid JSON for `T`.
+    #[cfg(feature = "json")]
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> T {
+        serde_json::from_slice(self.0.body()).expect("deserialize json")
+    }
+
+    /// The value of a cookie set by the response.
+    pub fn cookie(&self, name: &str) -> Option<&str> {
+        self.cookies()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Name and value of every cookie set by the response.
+    pub fn cookies(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| {
+                let pair = value.split(';').next()?;
+                let (name, value) = pair.split_once('=')?;
+                Some((name.trim(), value.trim().trim_matches('"')))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::BoxBody;
+    use http::Response;
+
+    async fn echo(mut req: Request<BoxBody>) -> Result<Response<BoxBody>, Error> {
+        if req.method() != Method::POST {
+            return Err(Error::new("wrong method"));
+        }
+
+        let mut resp = Response::new(std::mem::take(req.body_mut()));
+        resp.headers_mut().insert(
+            header::SET_COOKIE,
+            HeaderValue::from_static("session=abc; HttpOnly"),
+        );
+        Ok(resp)
+    }
+
+    #[tokio::test]
+    async fn test_request() {
+        let resp = request()
+            .method(Method::POST)
+            .path("/echo?q=1")
+            .text("hello")
+            .send(&echo)
+            .await;
+
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.text(), "hello");
+        assert_eq!(resp.cookie("session"), Some("abc"));
+
+        let err = request().try_send(&echo).await.unwrap_err();
+        assert!(err.to_string().contains("wrong method"));
+
+        let resp = request().send(&echo).await;
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(resp.text(), "500 Internal Server Error");
+
+        let next = |req: Request<BoxBody>| async move { crate::Outcome::Next(req) };
+        let resp = request().send(&next).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+}
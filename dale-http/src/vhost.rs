@@ -0,0 +1,320 @@
+//! Dispatching on the requested host name.
+//!
+//! The host is taken from the request URI, which carries the `:authority`
+//! of HTTP/2 requests, or else from the `Host` header. Patterns are matched
+//! label by label, ignoring case and port:
+//!
+//! - `example.com` matches the name exactly.
+//! - `*.example.com` matches any subdomain of `example.com`, but not
+//!   `example.com` itself.
+//! - `:tenant.example.com` matches a single label, and stores it as the
+//!   route parameter `tenant`.
+//!
+//! Exact names are tried before patterns, and patterns in the order they
+//! were added. Requests no matching service answers go to the fallback
+//! service, if any.
+use crate::{
+    rejection::{Rejection, Rejections},
+    router::{IntoRouteService, Params, RouteService},
+    Body, Outcome,
+};
+use dale::{boxed::BoxFuture, Describe, Description, IntoService, Service};
+use http::{header, Request};
+use router::Params as _;
+use std::{borrow::Cow, convert::Infallible, fmt, sync::Arc};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Label {
+    Exact(String),
+    Capture(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern {
+    labels: Vec<Label>,
+    wildcard: bool,
+}
+
+impl Pattern {
+    fn parse(pattern: &str) -> Pattern {
+        let pattern = pattern.trim_end_matches('.').to_ascii_lowercase();
+        let (wildcard, rest) = match pattern.strip_prefix("*.") {
+            Some(rest) => (true, rest),
+            None => (false, pattern.as_str()),
+        };
+
+        let labels = rest
+            .split('.')
+            .map(|label| match label.strip_prefix(':') {
+                Some(name) => Label::Capture(name.to_string()),
+                None => Label::Exact(label.to_string()),
+            })
+            .collect();
+
+        Pattern { labels, wildcard }
+    }
+
+    fn is_exact(&self) -> bool {
+        !self.wildcard
+            && self
+                .labels
+                .iter()
+                .all(|label| matches!(label, Label::Exact(_)))
+    }
+
+    fn matches(&self, host: &str, params: &mut Params) -> bool {
+        let labels = host.split('.').collect::<Vec<_>>();
+        let fits = if self.wildcard {
+            labels.len() > self.labels.len()
+        } else {
+            labels.len() == self.labels.len()
+        };
+        if !fits {
+            return false;
+        }
+
+        let labels = &labels[labels.len() - self.labels.len()..];
+        let mut captures = Vec::new();
+        for (label, value) in self.labels.iter().zip(labels) {
+            match label {
+                Label::Exact(name) if name == value => {}
+                Label::Capture(name) if !value.is_empty() => captures.push((name, *value)),
+                _ => return false,
+            }
+        }
+
+        for (name, value) in captures {
+            params.set(Cow::Owned(name.clone()), Cow::Owned(value.to_string()));
+        }
+
+        true
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.wildcard {
+            f.write_str("*.")?;
+        }
+        for (i, label) in self.labels.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            match label {
+                Label::Exact(name) => f.write_str(name)?,
+                Label::Capture(name) => write!(f, ":{}", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The requested host name, lowercased and without port.
+fn host<B>(req: &Request<B>) -> Option<String> {
+    let host = match req.uri().host() {
+        Some(host) => host,
+        None => {
+            let host = req.headers().get(header::HOST)?.to_str().ok()?;
+            match host.rsplit_once(':') {
+                Some((name, port))
+                    if port.bytes().all(|b| b.is_ascii_digit())
+                        && (!name.contains(':') || name.ends_with(']')) =>
+                {
+                    name
+                }
+                _ => host,
+            }
+        }
+    };
+
+    Some(host.trim_end_matches('.').to_ascii_lowercase())
+}
+
+pub struct Hosts<B> {
+    hosts: Vec<(Pattern, RouteService<B>)>,
+    fallback: Option<RouteService<B>>,
+}
+
+impl<B> fmt::Debug for Hosts<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hosts")
+            .field(
+                "hosts",
+                &self
+                    .hosts
+                    .iter()
+                    .map(|(pattern, _)| pattern.to_string())
+                    .collect::<Vec<_>>(),
+            )
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
+
+impl<B> Default for Hosts<B> {
+    fn default() -> Self {
+        Hosts::new()
+    }
+}
+
+impl<B> Hosts<B> {
+    pub fn new() -> Hosts<B> {
+        Hosts {
+            hosts: Vec::new(),
+            fallback: None,
+        }
+    }
+
+    /// Serves requests for hosts matching `pattern` with `service`.
+    pub fn host<S>(&mut self, pattern: &str, service: S) -> &mut Self
+    where
+        S: IntoRouteService<B, RouteService<B>>,
+    {
+        self.hosts
+            .push((Pattern::parse(pattern), service.into_route_service()));
+        self
+    }
+
+    /// Serves requests for all other hosts, or without a host, with `service`.
+    pub fn fallback<S>(&mut self, service: S) -> &mut Self
+    where
+        S: IntoRouteService<B, RouteService<B>>,
+    {
+        self.fallback = Some(service.into_route_service());
+        self
+    }
+}
+
+impl<B: Body + Send + Sync + 'static> IntoService<Request<B>> for Hosts<B> {
+    type Error = Infallible;
+    type Service = HostsService<B>;
+
+    fn into_service(mut self) -> Result<Self::Service, Self::Error> {
+        // Stable, so patterns keep their order behind the exact names.
+        self.hosts.sort_by_key(|(pattern, _)| !pattern.is_exact());
+        Ok(HostsService {
+            hosts: Arc::new(self),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct HostsService<B> {
+    hosts: Arc<Hosts<B>>,
+}
+
+impl<B> Clone for HostsService<B> {
+    fn clone(&self) -> Self {
+        HostsService {
+            hosts: self.hosts.clone(),
+        }
+    }
+}
+
+impl<B: Body + Send + Sync + 'static> Service<Request<B>> for HostsService<B> {
+    type Output = Outcome<B>;
+
+    type Future = BoxFuture<'static, Self::Output>;
+
+    fn call(&self, req: Request<B>) -> Self::Future {
+        let hosts = self.hosts.clone();
+        let mut req = req;
+        Box::pin(async move {
+            let outer = req.extensions().get::<Params>().cloned();
+            let name = host(&req);
+
+            for (pattern, service) in &hosts.hosts {
+                let mut params = outer.clone().unwrap_or_default();
+                match &name {
+                    Some(name) if pattern.matches(name, &mut params) => {}
+                    _ => continue,
+                }
+
+                req.extensions_mut().insert(params);
+                match service.call(req).await {
+                    dale::Outcome::Next(next) => req = next,
+                    o => return o,
+                }
+            }
+
+            match outer {
+                Some(params) => req.extensions_mut().insert(params),
+                None => req.extensions_mut().remove::<Params>(),
+            };
+
+            match &hosts.fallback {
+                Some(service) => service.call(req).await,
+                None => {
+                    Rejections::record(&mut req, Rejection::not_found());
+                    dale::Outcome::Next(req)
+                }
+            }
+        })
+    }
+}
+
+impl<B> Describe for HostsService<B> {
+    fn describe(&self) -> Description {
+        Description::new("hosts").with_children(
+            self.hosts
+                .hosts
+                .iter()
+                .map(|(pattern, _)| Description::new("host").with_detail(pattern)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{body::BoxBody, router::Router, router::Routing, test::request, RequestExt};
+
+    #[tokio::test]
+    async fn test_hosts() {
+        let mut tenant = Router::<BoxBody>::new();
+        tenant
+            .get("/users/:id", |req: Request<BoxBody>| async move {
+                let params = req.params();
+                format!(
+                    "{} {}",
+                    params.get("tenant").unwrap(),
+                    params.get("id").unwrap()
+                )
+            })
+            .unwrap();
+
+        let mut hosts = Hosts::<BoxBody>::new();
+        hosts
+            .host(":tenant.example.com", tenant.into_service().unwrap())
+            .host(
+                "*.static.example.com",
+                |_req: Request<BoxBody>| async move { "static" },
+            )
+            .host(
+                "www.example.com",
+                |_req: Request<BoxBody>| async move { "www" },
+            )
+            .fallback(|_req: Request<BoxBody>| async move { "default" });
+        let service = hosts.into_service().unwrap();
+
+        let send = |host: &'static str, path: &'static str| {
+            let service = service.clone();
+            async move {
+                request()
+                    .header("host", host)
+                    .path(path)
+                    .send(&service)
+                    .await
+                    .text()
+                    .to_string()
+            }
+        };
+
+        assert_eq!(send("acme.example.com:8080", "/users/1").await, "acme 1");
+        assert_eq!(send("WWW.example.com", "/").await, "www");
+        assert_eq!(send("a.b.static.example.com", "/").await, "static");
+        assert_eq!(send("static.example.com", "/").await, "default");
+        assert_eq!(send("acme.example.com", "/missing").await, "default");
+        assert_eq!(send("example.org", "/").await, "default");
+    }
+}
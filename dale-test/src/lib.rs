@@ -0,0 +1,18 @@
+mod executor;
+mod macros;
+mod mock;
+
+pub use self::{executor::*, mock::*};
+
+#[doc(hidden)]
+pub use dale;
+
+use dale::{IntoOutcome, Outcome, Service, ServiceFailure, ServiceSuccess};
+
+/// Call `service` and wait for the outcome on the current thread.
+pub fn call<S, R>(service: &S, req: R) -> Outcome<ServiceSuccess<R, S>, ServiceFailure<R, S>, R>
+where
+    S: Service<R>,
+{
+    block_on(service.call(req)).into_outcome()
+}
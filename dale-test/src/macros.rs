@@ -0,0 +1,59 @@
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __expect_outcome {
+    ($variant:ident, $service:expr, $req:expr) => {
+        match $crate::call(&$service, $req) {
+            $crate::dale::Outcome::$variant(ret) => ret,
+            $crate::dale::Outcome::Success(_) => {
+                panic!(concat!(
+                    "expected `",
+                    stringify!($variant),
+                    "`, got `Success`"
+                ))
+            }
+            $crate::dale::Outcome::Failure(_) => {
+                panic!(concat!(
+                    "expected `",
+                    stringify!($variant),
+                    "`, got `Failure`"
+                ))
+            }
+            $crate::dale::Outcome::Next(_) => {
+                panic!(concat!("expected `", stringify!($variant), "`, got `Next`"))
+            }
+        }
+    };
+}
+
+/// Call a service with a request and assert it succeeds. Evaluates to the
+/// success, or compares it to a third argument.
+#[macro_export]
+macro_rules! assert_success {
+    ($service:expr, $req:expr) => {
+        $crate::__expect_outcome!(Success, $service, $req)
+    };
+    ($service:expr, $req:expr, $expected:expr) => {
+        assert_eq!($crate::assert_success!($service, $req), $expected)
+    };
+}
+
+/// Call a service with a request and assert it fails. Evaluates to the
+/// failure, or compares it to a third argument.
+#[macro_export]
+macro_rules! assert_failure {
+    ($service:expr, $req:expr) => {
+        $crate::__expect_outcome!(Failure, $service, $req)
+    };
+    ($service:expr, $req:expr, $expected:expr) => {
+        assert_eq!($crate::assert_failure!($service, $req), $expected)
+    };
+}
+
+/// Call a service with a request and assert it passes the request on.
+/// Evaluates to the returned request.
+#[macro_export]
+macro_rules! assert_next {
+    ($service:expr, $req:expr) => {
+        $crate::__expect_outcome!(Next, $service, $req)
+    };
+}
@@ -0,0 +1,132 @@
+use dale::{Describe, Description, Outcome, Service};
+use std::{
+    collections::VecDeque,
+    fmt,
+    future::{ready, Ready},
+    sync::{Arc, Mutex},
+};
+
+/// What a [`MockService`] answers a single call with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Scripted<S, E> {
+    Success(S),
+    Failure(E),
+    Next,
+}
+
+struct Inner<R, S, E> {
+    script: VecDeque<Scripted<S, E>>,
+    requests: Vec<R>,
+    calls: usize,
+}
+
+/// A service answering with scripted outcomes.
+///
+/// Outcomes are returned in the order they were pushed. Once the script runs
+/// out, the request is passed on with `Next`. Clones share the script and the
+/// recorded requests, so keep one around to inspect after handing the other
+/// to the code under test.
+pub struct MockService<R, S, E> {
+    inner: Arc<Mutex<Inner<R, S, E>>>,
+}
+
+impl<R, S, E> MockService<R, S, E> {
+    pub fn new() -> MockService<R, S, E> {
+        MockService {
+            inner: Arc::new(Mutex::new(Inner {
+                script: VecDeque::new(),
+                requests: Vec::new(),
+                calls: 0,
+            })),
+        }
+    }
+
+    pub fn push(&self, outcome: Scripted<S, E>) -> &Self {
+        self.inner.lock().unwrap().script.push_back(outcome);
+        self
+    }
+
+    pub fn push_success(&self, success: S) -> &Self {
+        self.push(Scripted::Success(success))
+    }
+
+    pub fn push_failure(&self, failure: E) -> &Self {
+        self.push(Scripted::Failure(failure))
+    }
+
+    pub fn push_next(&self) -> &Self {
+        self.push(Scripted::Next)
+    }
+
+    /// Number of times the service was called.
+    pub fn calls(&self) -> usize {
+        self.inner.lock().unwrap().calls
+    }
+
+    /// Takes the requests answered with `Success` or `Failure`. Requests
+    /// passed on with `Next` are handed back to the caller, so they're only
+    /// counted by [`calls`](MockService::calls).
+    pub fn take_requests(&self) -> Vec<R> {
+        std::mem::take(&mut self.inner.lock().unwrap().requests)
+    }
+
+    /// Number of scripted outcomes not yet used.
+    pub fn remaining(&self) -> usize {
+        self.inner.lock().unwrap().script.len()
+    }
+}
+
+impl<R, S, E> Default for MockService<R, S, E> {
+    fn default() -> Self {
+        MockService::new()
+    }
+}
+
+impl<R, S, E> Clone for MockService<R, S, E> {
+    fn clone(&self) -> Self {
+        MockService {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<R, S, E> fmt::Debug for MockService<R, S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.inner.lock().unwrap();
+        f.debug_struct("MockService")
+            .field("calls", &inner.calls)
+            .field("remaining", &inner.script.len())
+            .finish()
+    }
+}
+
+impl<R, S, E> Service<R> for MockService<R, S, E> {
+    type Output = Outcome<S, E, R>;
+
+    type Future = Ready<Self::Output>;
+
+    fn call(&self, req: R) -> Self::Future {
+        let mut inner = self.inner.lock().unwrap();
+        inner.calls += 1;
+
+        let outcome = match inner.script.pop_front() {
+            Some(Scripted::Success(success)) => {
+                inner.requests.push(req);
+                Outcome::Success(success)
+            }
+            Some(Scripted::Failure(failure)) => {
+                inner.requests.push(req);
+                Outcome::Failure(failure)
+            }
+            Some(Scripted::Next) | None => Outcome::Next(req),
+        };
+
+        ready(outcome)
+    }
+}
+
+impl<R, S, E> Describe for MockService<R, S, E> {
+    fn describe(&self) -> Description {
+        Description::new("mock")
+    }
+}
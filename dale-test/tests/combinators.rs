@@ -0,0 +1,49 @@
+use dale::{Either, ServiceExt, VecService};
+use dale_test::{assert_failure, assert_next, assert_success, MockService};
+
+#[test]
+fn test_mock() {
+    let mock = MockService::<u32, &str, ()>::new();
+    mock.push_success("one").push_next().push_failure(());
+
+    assert_success!(mock, 1, "one");
+    assert_eq!(assert_next!(mock, 2), 2);
+    assert_failure!(mock, 3, ());
+    assert_next!(mock, 4);
+
+    assert_eq!(mock.calls(), 4);
+    assert_eq!(mock.take_requests(), vec![1, 3]);
+}
+
+#[test]
+fn test_or() {
+    let left = MockService::<u32, &str, ()>::new();
+    let right = MockService::<u32, u8, ()>::new();
+    left.push_next().push_success("left");
+    right.push_success(1);
+
+    let service = left.clone().or(right.clone());
+
+    assert_success!(service, 1, Either::Right(1));
+    assert_success!(service, 2, Either::Left("left"));
+    assert_next!(service, 3);
+
+    assert_eq!(left.calls(), 3);
+    assert_eq!(right.calls(), 2);
+}
+
+#[test]
+fn test_vec() {
+    let first = MockService::<u32, u8, ()>::new();
+    let second = MockService::<u32, u8, ()>::new();
+    first.push_next().push_failure(());
+    second.push_success(2);
+
+    let service = VecService::new(vec![first.clone(), second.clone()]);
+
+    assert_success!(service, 1, 2);
+    assert_failure!(service, 2);
+    assert_next!(service, 3);
+
+    assert_eq!(second.take_requests(), vec![1]);
+}
@@ -0,0 +1,541 @@
+use crate::{Failure, IntoOutcome, Middleware, Outcome, RequestKey, Service, Success};
+use core::{
+    fmt,
+    future::Future,
+    hash::Hash,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures_core::ready;
+use pin_project_lite::pin_project;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, Weak},
+    time::{Duration, Instant},
+};
+
+/// A bounded LRU map whose entries expire.
+pub struct CacheStore<K, V> {
+    map: HashMap<K, usize>,
+    nodes: Vec<Option<Node<K, V>>>,
+    free: Vec<usize>,
+    head: Option<usize>,
+    tail: Option<usize>,
+    capacity: usize,
+}
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    expires: Instant,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+impl<K: Hash + Eq + Clone, V> CacheStore<K, V> {
+    pub fn new(capacity: usize) -> CacheStore<K, V> {
+        CacheStore {
+            map: HashMap::new(),
+            nodes: Vec::new(),
+            free: Vec::new(),
+            head: None,
+            tail: None,
+            capacity,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Look up `key`, marking it as most recently used. Expired entries are
+    /// removed.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        let idx = *self.map.get(key)?;
+
+        if self.node(idx).expires <= Instant::now() {
+            self.remove(key);
+            return None;
+        }
+
+        self.unlink(idx);
+        self.push_front(idx);
+
+        Some(&self.node(idx).value)
+    }
+
+    /// Insert `value`, evicting the least recently used entry if full.
+    pub fn insert(&mut self, key: K, value: V, ttl: Duration) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let expires = Instant::now() + ttl;
+
+        if let Some(&idx) = self.map.get(&key) {
+            let node = self.node_mut(idx);
+            node.value = value;
+            node.expires = expires;
+            self.unlink(idx);
+            self.push_front(idx);
+            return;
+        }
+
+        if self.map.len() >= self.capacity {
+            if let Some(tail) = self.tail {
+                let key = self.node(tail).key.clone();
+                self.remove(&key);
+            }
+        }
+
+        let node = Node {
+            key: key.clone(),
+            value,
+            expires,
+            prev: None,
+            next: None,
+        };
+
+        let idx = match self.free.pop() {
+            Some(idx) => {
+                self.nodes[idx] = Some(node);
+                idx
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        };
+
+        self.push_front(idx);
+        self.map.insert(key, idx);
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let idx = self.map.remove(key)?;
+        self.unlink(idx);
+        self.free.push(idx);
+        self.nodes[idx].take().map(|node| node.value)
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.nodes.clear();
+        self.free.clear();
+        self.head = None;
+        self.tail = None;
+    }
+
+    fn node(&self, idx: usize) -> &Node<K, V> {
+        self.nodes[idx].as_ref().expect("linked node")
+    }
+
+    fn node_mut(&mut self, idx: usize) -> &mut Node<K, V> {
+        self.nodes[idx].as_mut().expect("linked node")
+    }
+
+    fn unlink(&mut self, idx: usize) {
+        let (prev, next) = {
+            let node = self.node(idx);
+            (node.prev, node.next)
+        };
+
+        match prev {
+            Some(prev) => self.node_mut(prev).next = next,
+            None => self.head = next,
+        }
+
+        match next {
+            Some(next) => self.node_mut(next).prev = prev,
+            None => self.tail = prev,
+        }
+    }
+
+    fn push_front(&mut self, idx: usize) {
+        let head = self.head;
+        {
+            let node = self.node_mut(idx);
+            node.prev = None;
+            node.next = head;
+        }
+
+        match head {
+            Some(head) => self.node_mut(head).prev = Some(idx),
+            None => self.tail = Some(idx),
+        }
+
+        self.head = Some(idx);
+    }
+}
+
+impl<K, V> fmt::Debug for CacheStore<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CacheStore")
+            .field("len", &self.map.len())
+            .field("capacity", &self.capacity)
+            .finish()
+    }
+}
+
+trait Invalidate<K>: Send + Sync {
+    fn invalidate(&self, key: &K);
+    fn clear(&self);
+}
+
+impl<K, V> Invalidate<K> for Mutex<CacheStore<K, V>>
+where
+    K: Hash + Eq + Clone + Send,
+    V: Send,
+{
+    fn invalidate(&self, key: &K) {
+        self.lock().unwrap().remove(key);
+    }
+
+    fn clear(&self) {
+        self.lock().unwrap().clear();
+    }
+}
+
+/// Invalidates entries in every store created by a cache middleware.
+pub struct CacheHandle<K> {
+    stores: Arc<Mutex<Vec<Weak<dyn Invalidate<K>>>>>,
+}
+
+impl<K> Clone for CacheHandle<K> {
+    fn clone(&self) -> Self {
+        CacheHandle {
+            stores: self.stores.clone(),
+        }
+    }
+}
+
+impl<K> Default for CacheHandle<K> {
+    fn default() -> Self {
+        CacheHandle {
+            stores: Arc::default(),
+        }
+    }
+}
+
+impl<K> fmt::Debug for CacheHandle<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CacheHandle").finish_non_exhaustive()
+    }
+}
+
+impl<K: 'static> CacheHandle<K> {
+    pub fn new() -> CacheHandle<K> {
+        CacheHandle::default()
+    }
+
+    /// Have this handle invalidate entries in `store` too.
+    pub fn register<V>(&self, store: &Arc<Mutex<CacheStore<K, V>>>)
+    where
+        K: Hash + Eq + Clone + Send,
+        V: Send + 'static,
+    {
+        let store: Arc<dyn Invalidate<K>> = store.clone();
+        let mut stores = self.stores.lock().unwrap();
+        stores.retain(|store| store.strong_count() > 0);
+        stores.push(Arc::downgrade(&store));
+    }
+
+    pub fn invalidate(&self, key: &K) {
+        self.each(|store| store.invalidate(key));
+    }
+
+    pub fn clear(&self) {
+        self.each(|store| store.clear());
+    }
+
+    fn each(&self, func: impl Fn(&dyn Invalidate<K>)) {
+        for store in self.stores.lock().unwrap().iter() {
+            if let Some(store) = store.upgrade() {
+                func(&*store);
+            }
+        }
+    }
+}
+
+/// Decides how long a successful output stays cached. `None` skips caching it.
+pub trait CachePolicy<S> {
+    fn ttl(&self, value: &S) -> Option<Duration>;
+}
+
+impl<S> CachePolicy<S> for Duration {
+    fn ttl(&self, _value: &S) -> Option<Duration> {
+        Some(*self)
+    }
+}
+
+impl<S, F> CachePolicy<S> for F
+where
+    F: Fn(&S) -> Option<Duration>,
+{
+    fn ttl(&self, value: &S) -> Option<Duration> {
+        (self)(value)
+    }
+}
+
+/// Memoizes successful outputs by a key derived from the request.
+#[derive(Debug, Clone)]
+pub struct Cache<K, F, P = Duration> {
+    key: F,
+    policy: P,
+    capacity: usize,
+    handle: CacheHandle<K>,
+}
+
+impl<K: 'static, F> Cache<K, F> {
+    pub fn new(key: F, ttl: Duration) -> Cache<K, F> {
+        Cache {
+            key,
+            policy: ttl,
+            capacity: 1024,
+            handle: CacheHandle::new(),
+        }
+    }
+}
+
+impl<K, F, P> Cache<K, F, P> {
+    /// The maximum number of entries kept, per wrapped service.
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Decide the time to live per output.
+    pub fn policy<U>(self, policy: U) -> Cache<K, F, U> {
+        Cache {
+            key: self.key,
+            policy,
+            capacity: self.capacity,
+            handle: self.handle,
+        }
+    }
+
+    pub fn handle(&self) -> CacheHandle<K> {
+        self.handle.clone()
+    }
+}
+
+impl<R, T, K, F, P> Middleware<R, T> for Cache<K, F, P>
+where
+    T: Service<R>,
+    F: RequestKey<R, Key = K> + Clone,
+    K: Hash + Eq + Clone + Send + 'static,
+    P: CachePolicy<Success<R, T::Output>> + Clone,
+    Success<R, T::Output>: Clone + Send + 'static,
+{
+    type Service = CacheService<T, F, K, Success<R, T::Output>, P>;
+
+    fn wrap(&self, service: T) -> Self::Service {
+        let store = Arc::new(Mutex::new(CacheStore::new(self.capacity)));
+        self.handle.register(&store);
+
+        CacheService {
+            service,
+            key: self.key.clone(),
+            policy: self.policy.clone(),
+            store,
+        }
+    }
+}
+
+pub struct CacheService<T, F, K, S, P> {
+    service: T,
+    key: F,
+    policy: P,
+    store: Arc<Mutex<CacheStore<K, S>>>,
+}
+
+impl<T: Clone, F: Clone, K, S, P: Clone> Clone for CacheService<T, F, K, S, P> {
+    fn clone(&self) -> Self {
+        CacheService {
+            service: self.service.clone(),
+            key: self.key.clone(),
+            policy: self.policy.clone(),
+            store: self.store.clone(),
+        }
+    }
+}
+
+impl<T, F, K, S, P, R> Service<R> for CacheService<T, F, K, S, P>
+where
+    T: Service<R>,
+    T::Output: IntoOutcome<R, Success = S>,
+    F: RequestKey<R, Key = K>,
+    K: Hash + Eq + Clone,
+    S: Clone,
+    P: CachePolicy<S> + Clone,
+{
+    type Output = Outcome<S, Failure<R, T::Output>, R>;
+
+    type Future = CacheFuture<T, R, K, P>;
+
+    fn call(&self, req: R) -> Self::Future {
+        let key = match self.key.key(&req) {
+            Some(key) => key,
+            None => {
+                return CacheFuture {
+                    state: State::Call {
+                        future: self.service.call(req),
+                    },
+                }
+            }
+        };
+
+        if let Some(value) = self.store.lock().unwrap().get(&key) {
+            return CacheFuture {
+                state: State::Hit {
+                    value: Some(value.clone()),
+                },
+            };
+        }
+
+        CacheFuture {
+            state: State::Miss {
+                future: self.service.call(req),
+                insert: Some(Insert {
+                    key,
+                    store: self.store.clone(),
+                    policy: self.policy.clone(),
+                }),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, F, K, S, P> crate::Describe for CacheService<T, F, K, S, P>
+where
+    T: crate::Describe,
+{
+    fn describe(&self) -> crate::Description {
+        crate::Description::new("cache").with_child(self.service.describe())
+    }
+}
+
+struct Insert<K, S, P> {
+    key: K,
+    store: Arc<Mutex<CacheStore<K, S>>>,
+    policy: P,
+}
+
+pin_project! {
+    pub struct CacheFuture<T, R, K, P>
+    where
+        T: Service<R>,
+    {
+        #[pin]
+        state: State<T, R, K, P>,
+    }
+}
+
+pin_project! {
+    #[project = StateProj]
+    enum State<T, R, K, P>
+    where
+        T: Service<R>,
+    {
+        Hit {
+            value: Option<Success<R, T::Output>>,
+        },
+        Miss {
+            #[pin]
+            future: T::Future,
+            insert: Option<Insert<K, Success<R, T::Output>, P>>,
+        },
+        Call {
+            #[pin]
+            future: T::Future,
+        },
+    }
+}
+
+impl<T, R, K, P> Future for CacheFuture<T, R, K, P>
+where
+    T: Service<R>,
+    K: Hash + Eq + Clone,
+    Success<R, T::Output>: Clone,
+    P: CachePolicy<Success<R, T::Output>>,
+{
+    type Output = Outcome<Success<R, T::Output>, Failure<R, T::Output>, R>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project().state.project() {
+            StateProj::Hit { value } => {
+                Poll::Ready(Outcome::Success(value.take().expect("poll after done")))
+            }
+            StateProj::Miss { future, insert } => {
+                let outcome = ready!(future.poll(cx)).into_outcome();
+                let insert = insert.take().expect("poll after done");
+                if let Outcome::Success(success) = &outcome {
+                    if let Some(ttl) = insert.policy.ttl(success) {
+                        insert
+                            .store
+                            .lock()
+                            .unwrap()
+                            .insert(insert.key, success.clone(), ttl);
+                    }
+                }
+                Poll::Ready(outcome)
+            }
+            StateProj::Call { future } => Poll::Ready(ready!(future.poll(cx)).into_outcome()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::{
+        convert::Infallible,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+    use futures_executor::block_on;
+
+    #[test]
+    fn test_store_evicts_lru() {
+        let mut store = CacheStore::new(2);
+        store.insert(1, "one", Duration::from_secs(60));
+        store.insert(2, "two", Duration::from_secs(60));
+        store.get(&1);
+        store.insert(3, "three", Duration::from_secs(60));
+
+        assert_eq!(store.get(&2), None);
+        assert_eq!(store.get(&1), Some(&"one"));
+        assert_eq!(store.get(&3), Some(&"three"));
+
+        store.insert(4, "four", Duration::ZERO);
+        assert_eq!(store.get(&4), None);
+    }
+
+    #[test]
+    fn test_cache() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = {
+            let calls = calls.clone();
+            move |req: u32| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                async move { Outcome::<_, Infallible, u32>::Success(req * 2) }
+            }
+        };
+
+        let cache = Cache::new(|req: &u32| Some(*req), Duration::from_secs(60));
+        let handle = cache.handle();
+        let service = cache.wrap(service);
+
+        assert_eq!(block_on(service.call(1)), Outcome::Success(2));
+        assert_eq!(block_on(service.call(1)), Outcome::Success(2));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        handle.invalidate(&1);
+        assert_eq!(block_on(service.call(1)), Outcome::Success(2));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
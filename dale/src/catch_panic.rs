@@ -0,0 +1,175 @@
+use crate::{Either, Failure, IntoOutcome, Middleware, Outcome, Service, Success};
+use core::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use futures_core::ready;
+use pin_project_lite::pin_project;
+use std::{
+    any::Any,
+    error::Error as StdError,
+    fmt,
+    panic::{catch_unwind, AssertUnwindSafe},
+};
+
+/// A panic caught while calling or polling a service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Panic {
+    message: String,
+}
+
+impl Panic {
+    pub fn new(payload: Box<dyn Any + Send>) -> Panic {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => match payload.downcast::<&'static str>() {
+                Ok(message) => String::from(*message),
+                Err(_) => String::from("Box<dyn Any>"),
+            },
+        };
+
+        Panic { message }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Panic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "panicked: {}", self.message)
+    }
+}
+
+impl StdError for Panic {}
+
+/// Middleware failing with a [`Panic`] instead of unwinding when the wrapped
+/// service panics.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CatchPanic;
+
+impl<R, T> Middleware<R, T> for CatchPanic
+where
+    T: Service<R>,
+{
+    type Service = CatchPanicService<T>;
+
+    fn wrap(&self, service: T) -> Self::Service {
+        CatchPanicService { service }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CatchPanicService<T> {
+    service: T,
+}
+
+impl<R, T> Service<R> for CatchPanicService<T>
+where
+    T: Service<R>,
+{
+    type Output = Outcome<Success<R, T::Output>, Either<Failure<R, T::Output>, Panic>, R>;
+
+    type Future = CatchPanicFuture<T::Future, R>;
+
+    fn call(&self, req: R) -> Self::Future {
+        let state = match catch_unwind(AssertUnwindSafe(|| self.service.call(req))) {
+            Ok(future) => State::Future { future },
+            Err(payload) => State::Panicked {
+                panic: Some(Panic::new(payload)),
+            },
+        };
+
+        CatchPanicFuture {
+            state,
+            _r: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> crate::Describe for CatchPanicService<T>
+where
+    T: crate::Describe,
+{
+    fn describe(&self) -> crate::Description {
+        crate::Description::new("catch_panic").with_child(self.service.describe())
+    }
+}
+
+pin_project! {
+    #[project = StateProj]
+    enum State<F> {
+        Future {
+            #[pin]
+            future: F,
+        },
+        Panicked {
+            panic: Option<Panic>,
+        },
+    }
+}
+
+pin_project! {
+    pub struct CatchPanicFuture<F, R> {
+        #[pin]
+        state: State<F>,
+        _r: PhantomData<fn(R)>,
+    }
+}
+
+impl<F, R> Future for CatchPanicFuture<F, R>
+where
+    F: Future,
+    F::Output: IntoOutcome<R>,
+{
+    type Output = Outcome<Success<R, F::Output>, Either<Failure<R, F::Output>, Panic>, R>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let ret = match this.state.project() {
+            StateProj::Future { future } => {
+                match catch_unwind(AssertUnwindSafe(|| future.poll(cx))) {
+                    Ok(output) => match ready!(output).into_outcome() {
+                        Outcome::Success(success) => Outcome::Success(success),
+                        Outcome::Failure(failure) => Outcome::Failure(Either::Left(failure)),
+                        Outcome::Next(next) => Outcome::Next(next),
+                    },
+                    Err(payload) => Outcome::Failure(Either::Right(Panic::new(payload))),
+                }
+            }
+            StateProj::Panicked { panic } => {
+                Outcome::Failure(Either::Right(panic.take().expect("poll after done")))
+            }
+        };
+
+        Poll::Ready(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_executor::block_on;
+
+    #[test]
+    fn test_catch_panic() {
+        let service = CatchPanic.wrap(|req: u32| async move {
+            if req == 0 {
+                panic!("division by zero");
+            }
+            Outcome::<u32, (), u32>::Success(100 / req)
+        });
+
+        assert_eq!(block_on(service.call(4)).success(), Some(25));
+        match block_on(service.call(0)) {
+            Outcome::Failure(Either::Right(panic)) => {
+                assert_eq!(panic.message(), "division by zero")
+            }
+            _ => panic!("expected a panic"),
+        }
+    }
+}
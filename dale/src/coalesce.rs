@@ -0,0 +1,326 @@
+use crate::{Failure, IntoOutcome, Middleware, Outcome, RequestKey, Service, Success};
+use core::{
+    future::Future,
+    hash::Hash,
+    mem,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+use futures_core::ready;
+use pin_project_lite::pin_project;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// The key [`Coalesce`] groups requests by. The same trait keys
+/// [`Cache`](crate::Cache).
+pub use crate::request_key::RequestKey as CoalesceKey;
+
+/// Single-flight middleware.
+///
+/// Concurrent requests with the same key share one in-flight call to the
+/// inner service, and every waiter gets a clone of its result. Requests
+/// without a key are never coalesced. If the leading call passes the request
+/// on, so does every waiter, with its own request.
+#[derive(Debug, Clone, Copy)]
+pub struct Coalesce<F> {
+    key: F,
+}
+
+impl<F> Coalesce<F> {
+    pub fn new(key: F) -> Coalesce<F> {
+        Coalesce { key }
+    }
+}
+
+impl<R, T, F> Middleware<R, T> for Coalesce<F>
+where
+    T: Service<R>,
+    F: RequestKey<R> + Clone,
+    Success<R, T::Output>: Clone,
+    Failure<R, T::Output>: Clone,
+{
+    type Service = CoalesceService<T, F, F::Key, Success<R, T::Output>, Failure<R, T::Output>>;
+
+    fn wrap(&self, service: T) -> Self::Service {
+        CoalesceService {
+            service: Arc::new(service),
+            key: self.key.clone(),
+            inflight: Arc::default(),
+        }
+    }
+}
+
+type Inflight<K, S, E> = Arc<Mutex<HashMap<K, Arc<Flight<S, E>>>>>;
+
+pub struct CoalesceService<T, F, K, S, E> {
+    service: Arc<T>,
+    key: F,
+    inflight: Inflight<K, S, E>,
+}
+
+impl<T, F: Clone, K, S, E> Clone for CoalesceService<T, F, K, S, E> {
+    fn clone(&self) -> Self {
+        CoalesceService {
+            service: self.service.clone(),
+            key: self.key.clone(),
+            inflight: self.inflight.clone(),
+        }
+    }
+}
+
+impl<T, F, K, S, E, R> Service<R> for CoalesceService<T, F, K, S, E>
+where
+    T: Service<R>,
+    T::Output: IntoOutcome<R, Success = S, Failure = E>,
+    F: RequestKey<R, Key = K>,
+    K: Hash + Eq + Clone,
+    S: Clone,
+    E: Clone,
+{
+    type Output = Outcome<S, E, R>;
+
+    type Future = CoalesceFuture<T, R, K>;
+
+    fn call(&self, req: R) -> Self::Future {
+        let key = match self.key.key(&req) {
+            Some(key) => key,
+            None => {
+                return CoalesceFuture {
+                    state: State::Call {
+                        future: self.service.call(req),
+                    },
+                }
+            }
+        };
+
+        let mut inflight = self.inflight.lock().unwrap();
+
+        let state = match inflight.get(&key) {
+            Some(flight) => State::Wait {
+                flight: flight.clone(),
+                service: self.service.clone(),
+                req: Some(req),
+            },
+            None => {
+                let flight = Arc::new(Flight {
+                    state: Mutex::new(FlightState::Pending(Vec::new())),
+                });
+                inflight.insert(key.clone(), flight.clone());
+                drop(inflight);
+
+                State::Lead {
+                    future: self.service.call(req),
+                    leader: Some(Leader {
+                        key,
+                        flight,
+                        inflight: self.inflight.clone(),
+                        done: false,
+                    }),
+                }
+            }
+        };
+
+        CoalesceFuture { state }
+    }
+}
+
+struct Flight<S, E> {
+    state: Mutex<FlightState<S, E>>,
+}
+
+enum FlightState<S, E> {
+    Pending(Vec<Waker>),
+    // None when the leader passed its request on
+    Done(Option<Result<S, E>>),
+    Abandoned,
+}
+
+struct Leader<K: Hash + Eq, S, E> {
+    key: K,
+    flight: Arc<Flight<S, E>>,
+    inflight: Inflight<K, S, E>,
+    done: bool,
+}
+
+impl<K: Hash + Eq, S, E> Leader<K, S, E> {
+    fn finish(mut self, result: Option<Result<S, E>>) {
+        self.done = true;
+        self.release(FlightState::Done(result));
+    }
+
+    fn release(&self, state: FlightState<S, E>) {
+        self.inflight.lock().unwrap().remove(&self.key);
+        let prev = mem::replace(&mut *self.flight.state.lock().unwrap(), state);
+        if let FlightState::Pending(wakers) = prev {
+            for waker in wakers {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl<K: Hash + Eq, S, E> Drop for Leader<K, S, E> {
+    fn drop(&mut self) {
+        // The leading call was cancelled, let the waiters call the service themselves
+        if !self.done {
+            self.release(FlightState::Abandoned);
+        }
+    }
+}
+
+pin_project! {
+    pub struct CoalesceFuture<T, R, K>
+    where
+        T: Service<R>,
+        K: Hash,
+        K: Eq,
+    {
+        #[pin]
+        state: State<T, R, K>,
+    }
+}
+
+pin_project! {
+    #[project = StateProj]
+    enum State<T, R, K>
+    where
+        T: Service<R>,
+        K: Hash,
+        K: Eq,
+    {
+        Lead {
+            #[pin]
+            future: T::Future,
+            leader: Option<Leader<K, Success<R, T::Output>, Failure<R, T::Output>>>,
+        },
+        Wait {
+            flight: Arc<Flight<Success<R, T::Output>, Failure<R, T::Output>>>,
+            service: Arc<T>,
+            req: Option<R>,
+        },
+        Call {
+            #[pin]
+            future: T::Future,
+        },
+    }
+}
+
+impl<T, R, K> Future for CoalesceFuture<T, R, K>
+where
+    T: Service<R>,
+    K: Hash + Eq,
+    Success<R, T::Output>: Clone,
+    Failure<R, T::Output>: Clone,
+{
+    type Output = Outcome<Success<R, T::Output>, Failure<R, T::Output>, R>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            let this = self.as_mut().project();
+
+            let next = match this.state.project() {
+                StateProj::Lead { future, leader } => {
+                    let outcome = ready!(future.poll(cx)).into_outcome();
+                    let leader = leader.take().expect("poll after done");
+                    match &outcome {
+                        Outcome::Success(success) => leader.finish(Some(Ok(success.clone()))),
+                        Outcome::Failure(failure) => leader.finish(Some(Err(failure.clone()))),
+                        Outcome::Next(_) => leader.finish(None),
+                    }
+                    return Poll::Ready(outcome);
+                }
+                StateProj::Wait {
+                    flight,
+                    service,
+                    req,
+                } => {
+                    let mut state = flight.state.lock().unwrap();
+                    match &mut *state {
+                        FlightState::Pending(wakers) => {
+                            if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                                wakers.push(cx.waker().clone());
+                            }
+                            return Poll::Pending;
+                        }
+                        FlightState::Done(result) => {
+                            let req = req.take().expect("poll after done");
+                            return Poll::Ready(match result {
+                                Some(Ok(success)) => Outcome::Success(success.clone()),
+                                Some(Err(failure)) => Outcome::Failure(failure.clone()),
+                                None => Outcome::Next(req),
+                            });
+                        }
+                        FlightState::Abandoned => State::Call {
+                            future: service.call(req.take().expect("poll after done")),
+                        },
+                    }
+                }
+                StateProj::Call { future } => {
+                    return Poll::Ready(ready!(future.poll(cx)).into_outcome())
+                }
+            };
+
+            self.as_mut().project().state.set(next);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, F, K, S, E> crate::Describe for CoalesceService<T, F, K, S, E>
+where
+    T: crate::Describe,
+{
+    fn describe(&self) -> crate::Description {
+        crate::Description::new("coalesce").with_child(self.service.describe())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::{
+        convert::Infallible,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+    use futures_executor::block_on;
+
+    fn service(
+        calls: Arc<AtomicUsize>,
+    ) -> impl Service<u32, Output = Outcome<u32, Infallible, u32>> {
+        let service = move |req: u32| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async move { Outcome::Success(req * 2) }
+        };
+        Coalesce::new(|req: &u32| Some(*req)).wrap(service)
+    }
+
+    #[test]
+    fn test_coalesce() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = service(calls.clone());
+
+        let leader = service.call(1);
+        let waiter = service.call(1);
+        let other = service.call(2);
+
+        assert_eq!(block_on(leader), Outcome::Success(2));
+        assert_eq!(block_on(waiter), Outcome::Success(2));
+        assert_eq!(block_on(other), Outcome::Success(4));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_abandoned() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = service(calls.clone());
+
+        let leader = service.call(1);
+        let waiter = service.call(1);
+        drop(leader);
+
+        assert_eq!(block_on(waiter), Outcome::Success(2));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -95,6 +95,16 @@ where
     }
 }
 
+#[cfg(feature = "alloc")]
+impl<S, E> crate::Describe for ErrInto<S, E>
+where
+    S: crate::Describe,
+{
+    fn describe(&self) -> crate::Description {
+        self.service.describe()
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
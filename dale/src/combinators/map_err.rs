@@ -90,3 +90,13 @@ where
         Poll::Ready(ret)
     }
 }
+
+#[cfg(feature = "alloc")]
+impl<F, S, E> crate::Describe for MapErr<F, S, E>
+where
+    S: crate::Describe,
+{
+    fn describe(&self) -> crate::Description {
+        self.service.describe()
+    }
+}
@@ -146,3 +146,16 @@ where
         }
     }
 }
+
+#[cfg(feature = "alloc")]
+impl<T1, T2, I> crate::Describe for Or<T1, T2, I>
+where
+    T1: crate::Describe,
+    T2: crate::Describe,
+{
+    fn describe(&self) -> crate::Description {
+        crate::Description::new("or")
+            .with_child(self.left.describe())
+            .with_child(self.right.describe())
+    }
+}
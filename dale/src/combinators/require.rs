@@ -105,3 +105,13 @@ where
         }
     }
 }
+
+#[cfg(feature = "alloc")]
+impl<T, F> crate::Describe for RequireService<T, F>
+where
+    T: crate::Describe,
+{
+    fn describe(&self) -> crate::Description {
+        crate::Description::new("require").with_child(self.service.describe())
+    }
+}
@@ -62,3 +62,23 @@ where
         self.service.call(req)
     }
 }
+
+#[cfg(feature = "alloc")]
+impl<T> crate::Describe for SharedService<T>
+where
+    T: crate::Describe,
+{
+    fn describe(&self) -> crate::Description {
+        self.service.describe()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> crate::Describe for LocalSharedService<T>
+where
+    T: crate::Describe,
+{
+    fn describe(&self) -> crate::Description {
+        self.service.describe()
+    }
+}
@@ -131,3 +131,13 @@ where
         }
     }
 }
+
+#[cfg(feature = "alloc")]
+impl<S, F> crate::Describe for Then<S, F>
+where
+    S: crate::Describe,
+{
+    fn describe(&self) -> crate::Description {
+        crate::Description::new("then").with_child(self.service.describe())
+    }
+}
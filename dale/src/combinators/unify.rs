@@ -60,3 +60,13 @@ where
         Poll::Ready(unified)
     }
 }
+
+#[cfg(feature = "alloc")]
+impl<F> crate::Describe for Unify<F>
+where
+    F: crate::Describe,
+{
+    fn describe(&self) -> crate::Description {
+        self.filter.describe()
+    }
+}
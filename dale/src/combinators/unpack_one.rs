@@ -72,3 +72,13 @@ where
         Poll::Ready(unified)
     }
 }
+
+#[cfg(feature = "alloc")]
+impl<S> crate::Describe for UnpackOne<S>
+where
+    S: crate::Describe,
+{
+    fn describe(&self) -> crate::Description {
+        self.filter.describe()
+    }
+}
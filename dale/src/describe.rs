@@ -0,0 +1,235 @@
+use crate::{types::alloc::*, Service};
+use core::fmt::{self, Write};
+
+/// A node in the tree produced by [`Describe::describe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Description {
+    name: Cow<'static, str>,
+    detail: Option<String>,
+    children: Vec<Description>,
+}
+
+impl Description {
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Description {
+        Description {
+            name: name.into(),
+            detail: None,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn with_detail(mut self, detail: impl fmt::Display) -> Description {
+        let mut out = String::new();
+        let _ = write!(out, "{}", detail);
+        self.detail = Some(out);
+        self
+    }
+
+    pub fn with_child(mut self, child: Description) -> Description {
+        self.children.push(child);
+        self
+    }
+
+    pub fn with_children(mut self, children: impl IntoIterator<Item = Description>) -> Description {
+        self.children.extend(children);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    pub fn children(&self) -> &[Description] {
+        &self.children
+    }
+
+    /// Render the tree as indented text, one node per line.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let _ = self.write_text(&mut out, 0);
+        out
+    }
+
+    /// Render the tree as a Graphviz DOT digraph.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        let _ = self.write_dot(&mut out);
+        out
+    }
+
+    fn label(&self, f: &mut impl Write) -> fmt::Result {
+        match &self.detail {
+            Some(detail) => write!(f, "{}: {}", self.name, detail),
+            None => write!(f, "{}", self.name),
+        }
+    }
+
+    fn write_text(&self, f: &mut impl Write, depth: usize) -> fmt::Result {
+        for _ in 0..depth {
+            f.write_str("  ")?;
+        }
+        self.label(f)?;
+        f.write_char('\n')?;
+        for child in &self.children {
+            child.write_text(f, depth + 1)?;
+        }
+        Ok(())
+    }
+
+    fn write_dot(&self, f: &mut impl Write) -> fmt::Result {
+        f.write_str("digraph service {\n")?;
+        f.write_str("  node [shape=box];\n")?;
+        let mut next = 0;
+        self.write_dot_node(f, &mut next)?;
+        f.write_str("}\n")
+    }
+
+    fn write_dot_node(&self, f: &mut impl Write, next: &mut usize) -> fmt::Result {
+        let id = *next;
+        *next += 1;
+
+        let mut label = String::new();
+        self.label(&mut label)?;
+        write!(f, "  n{} [label=\"", id)?;
+        for c in label.chars() {
+            match c {
+                '"' | '\\' => {
+                    f.write_char('\\')?;
+                    f.write_char(c)?;
+                }
+                '\n' => f.write_str("\\n")?,
+                c => f.write_char(c)?,
+            }
+        }
+        f.write_str("\"];\n")?;
+
+        for child in &self.children {
+            let child_id = *next;
+            child.write_dot_node(f, next)?;
+            writeln!(f, "  n{} -> n{};", id, child_id)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Description {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_text(f, 0)
+    }
+}
+
+/// Services that can report how they were composed.
+///
+/// Implemented by the combinators and filters in this crate. Leaf services
+/// like closures can be made describable with [`ServiceExt::named`](crate::ServiceExt::named).
+pub trait Describe {
+    fn describe(&self) -> Description;
+}
+
+impl<T: Describe + ?Sized> Describe for &T {
+    fn describe(&self) -> Description {
+        (**self).describe()
+    }
+}
+
+impl<T: Describe + ?Sized> Describe for Box<T> {
+    fn describe(&self) -> Description {
+        (**self).describe()
+    }
+}
+
+impl<T: Describe + ?Sized> Describe for Arc<T> {
+    fn describe(&self) -> Description {
+        (**self).describe()
+    }
+}
+
+impl<T: Describe> Describe for Option<T> {
+    fn describe(&self) -> Description {
+        match self {
+            Some(service) => service.describe(),
+            None => Description::new("none"),
+        }
+    }
+}
+
+impl<L: Describe, R: Describe> Describe for crate::Either<L, R> {
+    fn describe(&self) -> Description {
+        match self {
+            crate::Either::Left(left) => left.describe(),
+            crate::Either::Right(right) => right.describe(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Named<S> {
+    service: S,
+    name: Cow<'static, str>,
+}
+
+impl<S> Named<S> {
+    pub fn new(service: S, name: impl Into<Cow<'static, str>>) -> Named<S> {
+        Named {
+            service,
+            name: name.into(),
+        }
+    }
+}
+
+impl<S, R> Service<R> for Named<S>
+where
+    S: Service<R>,
+{
+    type Output = S::Output;
+    type Future = S::Future;
+
+    fn call(&self, req: R) -> Self::Future {
+        self.service.call(req)
+    }
+}
+
+impl<S> Describe for Named<S> {
+    fn describe(&self) -> Description {
+        Description::new(self.name.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{filters, ServiceExt};
+
+    fn service() -> impl Describe {
+        filters::any::<u32>()
+            .named("first")
+            .and(filters::any().named("second"))
+            .map(|| 42)
+            .or(filters::any::<u32>().named("fallback"))
+    }
+
+    #[test]
+    fn test_text() {
+        assert_eq!(
+            service().describe().to_text(),
+            "or\n  map\n    and\n      first\n      second\n  fallback\n"
+        );
+    }
+
+    #[test]
+    fn test_dot() {
+        let dot = Description::new("path")
+            .with_detail("\"/\"")
+            .with_child(Description::new("leaf"))
+            .to_dot();
+        assert_eq!(
+            dot,
+            "digraph service {\n  node [shape=box];\n  n0 [label=\"path: \\\"/\\\"\"];\n  n1 [label=\"leaf\"];\n  n0 -> n1;\n}\n"
+        );
+    }
+}
@@ -152,3 +152,16 @@ where
         }
     }
 }
+
+#[cfg(feature = "alloc")]
+impl<T, U> crate::Describe for And<T, U>
+where
+    T: crate::Describe,
+    U: crate::Describe,
+{
+    fn describe(&self) -> crate::Description {
+        crate::Description::new("and")
+            .with_child(self.first.describe())
+            .with_child(self.second.describe())
+    }
+}
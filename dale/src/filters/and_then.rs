@@ -141,3 +141,13 @@ where
         }
     }
 }
+
+#[cfg(feature = "alloc")]
+impl<S, F> crate::Describe for AndThen<S, F>
+where
+    S: crate::Describe,
+{
+    fn describe(&self) -> crate::Description {
+        crate::Description::new("and_then").with_child(self.service.describe())
+    }
+}
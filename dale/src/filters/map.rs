@@ -68,3 +68,13 @@ where
         }
     }
 }
+
+#[cfg(feature = "alloc")]
+impl<T, F> crate::Describe for Map<T, F>
+where
+    T: crate::Describe,
+{
+    fn describe(&self) -> crate::Description {
+        crate::Description::new("map").with_child(self.filter.describe())
+    }
+}
@@ -128,3 +128,12 @@ where
         }
     }
 }
+
+impl<S> crate::Describe for VecService<S>
+where
+    S: crate::Describe,
+{
+    fn describe(&self) -> crate::Description {
+        crate::Description::new("vec").with_children(self.0.iter().map(|s| s.describe()))
+    }
+}
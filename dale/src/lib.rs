@@ -10,11 +10,12 @@ mod impls;
 mod into_outcome;
 mod into_result;
 mod into_service;
-mod middleware;
+pub mod middleware;
 mod middleware_ext;
 mod outcome;
 mod service;
 mod service_ext;
+mod service_impl;
 mod types;
 
 mod future_ext;
@@ -26,14 +27,32 @@ pub mod filters;
 #[cfg(feature = "alloc")]
 pub mod boxed;
 
+#[cfg(feature = "alloc")]
+pub mod describe;
+
+#[cfg(feature = "alloc")]
+mod split;
+
+#[cfg(feature = "std")]
+mod cache;
+#[cfg(feature = "std")]
+mod catch_panic;
+#[cfg(feature = "std")]
+mod coalesce;
+#[cfg(feature = "std")]
+mod request_key;
+
 pub use self::{
     into_outcome::*, into_service::*, middleware::*, middleware_ext::*, outcome::*, service::*,
-    service_ext::*,
+    service_ext::*, service_impl::*,
 };
 
 #[cfg(feature = "alloc")]
 pub use self::boxed::BoxService;
 
+#[cfg(feature = "alloc")]
+pub use self::describe::{Describe, Description};
+
 mod outcome_impl;
 
 pub use either::Either;
@@ -43,3 +62,9 @@ pub use dale_derive::*;
 
 #[cfg(feature = "alloc")]
 pub use impls::*;
+
+#[cfg(feature = "alloc")]
+pub use split::*;
+
+#[cfg(feature = "std")]
+pub use self::{cache::*, catch_panic::*, coalesce::*, request_key::*};
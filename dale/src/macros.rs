@@ -8,6 +8,19 @@ macro_rules! fail {
     };
 }
 
+/// Unwrap a success, returning early with the failure (converted with
+/// `Into`, like [`fail!`]) or the next value otherwise.
+#[macro_export]
+macro_rules! try_outcome {
+    ($ret:expr) => {
+        match $crate::IntoOutcome::into_outcome($ret) {
+            $crate::Outcome::Success(ret) => ret,
+            $crate::Outcome::Failure(err) => return $crate::Outcome::Failure(err.into()),
+            $crate::Outcome::Next(next) => return $crate::Outcome::Next(next),
+        }
+    };
+}
+
 #[macro_export]
 macro_rules! success {
     ($expr: expr) => {
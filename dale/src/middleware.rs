@@ -1,5 +1,6 @@
 use crate::into_outcome::IntoOutcome;
 use crate::service::Service;
+use crate::{middleware_ext::And, Either};
 use crate::{ServiceFailure, ServiceSuccess};
 use core::future::Future;
 use core::marker::PhantomData;
@@ -12,6 +13,54 @@ pub trait Middleware<R, T: Service<R>> {
     fn wrap(&self, service: T) -> Self::Service;
 }
 
+#[cfg(feature = "alloc")]
+impl<R, T, M> Middleware<R, T> for crate::types::alloc::Box<M>
+where
+    M: Middleware<R, T> + ?Sized,
+    T: Service<R>,
+{
+    type Service = M::Service;
+    fn wrap(&self, service: T) -> Self::Service {
+        (**self).wrap(service)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<R, T, M> Middleware<R, T> for crate::types::alloc::Arc<M>
+where
+    M: Middleware<R, T> + ?Sized,
+    T: Service<R>,
+{
+    type Service = M::Service;
+    fn wrap(&self, service: T) -> Self::Service {
+        (**self).wrap(service)
+    }
+}
+
+/// `None` leaves the service as is.
+impl<R, T, M> Middleware<R, T> for Option<M>
+where
+    M: Middleware<R, T>,
+    T: Service<R>,
+{
+    type Service = Either<M::Service, T>;
+    fn wrap(&self, service: T) -> Self::Service {
+        match self {
+            Some(middleware) => Either::Left(middleware.wrap(service)),
+            None => Either::Right(service),
+        }
+    }
+}
+
+/// Apply `middleware` only when `cond` holds.
+pub fn when<M>(cond: bool, middleware: M) -> Option<M> {
+    if cond {
+        Some(middleware)
+    } else {
+        None
+    }
+}
+
 pub struct MiddlewareFn<R, F, T> {
     cb: F,
     _r: PhantomData<R>,
@@ -106,3 +155,110 @@ where
         service
     }
 }
+
+#[cfg(feature = "alloc")]
+impl<R, F, T> crate::Describe for MiddlewareFnService<R, F, T>
+where
+    T: crate::Describe,
+{
+    fn describe(&self) -> crate::Description {
+        crate::Description::new("wrap_fn").with_child(self.service.describe())
+    }
+}
+
+/// Composes middleware into a single one.
+///
+/// Each pushed middleware wraps the ones pushed before it, so the last one
+/// sees the request first.
+#[derive(Debug, Clone, Copy)]
+pub struct Stack<M = Passthrough> {
+    middleware: M,
+}
+
+impl Stack {
+    pub fn new() -> Stack {
+        Stack {
+            middleware: Passthrough,
+        }
+    }
+}
+
+impl Default for Stack {
+    fn default() -> Self {
+        Stack::new()
+    }
+}
+
+impl<M> Stack<M> {
+    pub fn push<N>(self, middleware: N) -> Stack<And<M, N>> {
+        Stack {
+            middleware: And::new(self.middleware, middleware),
+        }
+    }
+
+    pub fn push_if<N>(self, cond: bool, middleware: N) -> Stack<And<M, Option<N>>> {
+        self.push(when(cond, middleware))
+    }
+
+    pub fn into_inner(self) -> M {
+        self.middleware
+    }
+}
+
+impl<R, T, M> Middleware<R, T> for Stack<M>
+where
+    M: Middleware<R, T>,
+    T: Service<R>,
+{
+    type Service = M::Service;
+    fn wrap(&self, service: T) -> Self::Service {
+        self.middleware.wrap(service)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MiddlewareExt, Outcome, ServiceExt};
+    use futures_executor::block_on;
+
+    /// Appends a digit to the request on its way in.
+    #[derive(Clone, Copy)]
+    struct Digit(u32);
+
+    struct DigitService<T>(T, u32);
+
+    impl<T: Service<u32>> Middleware<u32, T> for Digit {
+        type Service = DigitService<T>;
+        fn wrap(&self, service: T) -> Self::Service {
+            DigitService(service, self.0)
+        }
+    }
+
+    impl<T: Service<u32>> Service<u32> for DigitService<T> {
+        type Output = T::Output;
+        type Future = T::Future;
+        fn call(&self, req: u32) -> Self::Future {
+            self.0.call(req * 10 + self.1)
+        }
+    }
+
+    async fn echo(req: u32) -> Result<u32, ()> {
+        Ok(req)
+    }
+
+    #[test]
+    fn test_stack() {
+        let stack = Stack::new()
+            .push(Digit(1))
+            .push_if(false, Digit(2))
+            .push(when(true, Digit(3)));
+
+        let service = stack.wrap(echo).unify().unify();
+        // The last pushed middleware sees the request first.
+        assert_eq!(block_on(service.call(0)), Outcome::Success(31));
+
+        let service = Stack::new().push(Digit(4)).boxed().wrap(echo);
+        assert_eq!(block_on(service.call(0)), Outcome::Success(4));
+    }
+}
@@ -47,10 +47,7 @@ where
         Self: Sized,
         M: Middleware<R, Self::Service>,
     {
-        And {
-            left: self,
-            right: middleware,
-        }
+        And::new(self, middleware)
     }
 }
 
@@ -61,18 +58,26 @@ where
 {
 }
 
-pub struct And<L, R> {
+/// `right` wraps the service produced by `left`.
+#[derive(Debug, Clone, Copy)]
+pub struct And<L, M> {
     left: L,
-    right: R,
+    right: M,
 }
 
-impl<L, R, S> Middleware<R, S> for And<L, R>
+impl<L, M> And<L, M> {
+    pub fn new(left: L, right: M) -> And<L, M> {
+        And { left, right }
+    }
+}
+
+impl<L, M, R, S> Middleware<R, S> for And<L, M>
 where
     L: Middleware<R, S>,
-    R: Middleware<R, L::Service>,
+    M: Middleware<R, L::Service>,
     S: Service<R>,
 {
-    type Service = R::Service;
+    type Service = M::Service;
 
     fn wrap(&self, service: S) -> Self::Service {
         self.right.wrap(self.left.wrap(service))
@@ -1,3 +1,5 @@
+use core::fmt;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Outcome<S, E, N> {
     Success(S),
@@ -6,6 +8,54 @@ pub enum Outcome<S, E, N> {
 }
 
 impl<S, E, N> Outcome<S, E, N> {
+    #[inline]
+    pub fn is_success(&self) -> bool {
+        matches!(self, Outcome::Success(_))
+    }
+
+    #[inline]
+    pub fn is_failure(&self) -> bool {
+        matches!(self, Outcome::Failure(_))
+    }
+
+    #[inline]
+    pub fn is_next(&self) -> bool {
+        matches!(self, Outcome::Next(_))
+    }
+
+    #[inline]
+    pub fn success(self) -> Option<S> {
+        match self {
+            Outcome::Success(success) => Some(success),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn failure(self) -> Option<E> {
+        match self {
+            Outcome::Failure(err) => Some(err),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn next(self) -> Option<N> {
+        match self {
+            Outcome::Next(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_ref(&self) -> Outcome<&S, &E, &N> {
+        match self {
+            Outcome::Success(success) => Outcome::Success(success),
+            Outcome::Failure(err) => Outcome::Failure(err),
+            Outcome::Next(n) => Outcome::Next(n),
+        }
+    }
+
     #[inline]
     pub fn map<F, O>(self, map: F) -> Outcome<O, E, N>
     where
@@ -54,6 +104,30 @@ impl<S, E, N> Outcome<S, E, N> {
         }
     }
 
+    #[inline]
+    pub fn and_then<F, O>(self, func: F) -> Outcome<O, E, N>
+    where
+        F: FnOnce(S) -> Outcome<O, E, N>,
+    {
+        match self {
+            Outcome::Success(success) => func(success),
+            Outcome::Failure(err) => Outcome::Failure(err),
+            Outcome::Next(n) => Outcome::Next(n),
+        }
+    }
+
+    #[inline]
+    pub fn or_else<F, O>(self, func: F) -> Outcome<S, O, N>
+    where
+        F: FnOnce(E) -> Outcome<S, O, N>,
+    {
+        match self {
+            Outcome::Success(success) => Outcome::Success(success),
+            Outcome::Failure(err) => func(err),
+            Outcome::Next(n) => Outcome::Next(n),
+        }
+    }
+
     pub fn map_next<F, M>(self, func: F) -> Outcome<S, E, M>
     where
         F: FnOnce(N) -> M,
@@ -83,4 +157,152 @@ impl<S, E, N> Outcome<S, E, N> {
             Outcome::Success(ret) => Ok(Some(ret)),
         }
     }
+
+    #[track_caller]
+    pub fn unwrap_success(self) -> S
+    where
+        E: fmt::Debug,
+        N: fmt::Debug,
+    {
+        match self {
+            Outcome::Success(success) => success,
+            Outcome::Failure(err) => {
+                panic!(
+                    "called `Outcome::unwrap_success()` on a `Failure` value: {:?}",
+                    err
+                )
+            }
+            Outcome::Next(n) => {
+                panic!(
+                    "called `Outcome::unwrap_success()` on a `Next` value: {:?}",
+                    n
+                )
+            }
+        }
+    }
+
+    #[track_caller]
+    pub fn unwrap_failure(self) -> E
+    where
+        S: fmt::Debug,
+        N: fmt::Debug,
+    {
+        match self {
+            Outcome::Failure(err) => err,
+            Outcome::Success(success) => panic!(
+                "called `Outcome::unwrap_failure()` on a `Success` value: {:?}",
+                success
+            ),
+            Outcome::Next(n) => {
+                panic!(
+                    "called `Outcome::unwrap_failure()` on a `Next` value: {:?}",
+                    n
+                )
+            }
+        }
+    }
+
+    #[track_caller]
+    pub fn unwrap_next(self) -> N
+    where
+        S: fmt::Debug,
+        E: fmt::Debug,
+    {
+        match self {
+            Outcome::Next(n) => n,
+            Outcome::Success(success) => panic!(
+                "called `Outcome::unwrap_next()` on a `Success` value: {:?}",
+                success
+            ),
+            Outcome::Failure(err) => {
+                panic!(
+                    "called `Outcome::unwrap_next()` on a `Failure` value: {:?}",
+                    err
+                )
+            }
+        }
+    }
+}
+
+impl<S, E, N> Outcome<Option<S>, E, N> {
+    /// `Success(None)` becomes `None`, everything else is wrapped in `Some`.
+    pub fn transpose(self) -> Option<Outcome<S, E, N>> {
+        match self {
+            Outcome::Success(Some(success)) => Some(Outcome::Success(success)),
+            Outcome::Success(None) => None,
+            Outcome::Failure(err) => Some(Outcome::Failure(err)),
+            Outcome::Next(n) => Some(Outcome::Next(n)),
+        }
+    }
+}
+
+impl<S, E, N> Outcome<Result<S, E>, E, N> {
+    /// `Success(Err(err))` becomes `Failure(err)`.
+    pub fn flatten(self) -> Outcome<S, E, N> {
+        match self {
+            Outcome::Success(Ok(success)) => Outcome::Success(success),
+            Outcome::Success(Err(err)) | Outcome::Failure(err) => Outcome::Failure(err),
+            Outcome::Next(n) => Outcome::Next(n),
+        }
+    }
+}
+
+impl<S, E, N> From<Result<S, E>> for Outcome<S, E, N> {
+    fn from(result: Result<S, E>) -> Self {
+        match result {
+            Ok(success) => Outcome::Success(success),
+            Err(err) => Outcome::Failure(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: Result<&str, u8>) -> Outcome<u32, u16, &str> {
+        let input = crate::try_outcome!(input);
+        match input.parse() {
+            Ok(n) => Outcome::Success(n),
+            Err(_) => Outcome::Next(input),
+        }
+    }
+
+    fn double(input: Outcome<u32, u16, &str>) -> Outcome<u32, u16, &str> {
+        let n = crate::try_outcome!(input);
+        Outcome::Success(n * 2)
+    }
+
+    #[test]
+    fn test_try_outcome() {
+        assert_eq!(double(parse(Ok("21"))), Outcome::Success(42));
+        assert_eq!(double(parse(Ok("nope"))), Outcome::Next("nope"));
+        assert_eq!(double(parse(Err(7))), Outcome::Failure(7));
+    }
+
+    #[test]
+    fn test_combinators() {
+        let outcome: Outcome<u32, u16, ()> = Ok(2).into();
+        assert!(outcome.is_success());
+        assert_eq!(
+            outcome
+                .and_then(|n| Outcome::<u32, _, _>::Failure(n as u16))
+                .unwrap_failure(),
+            2
+        );
+        assert_eq!(
+            Outcome::<u32, u16, ()>::Failure(1)
+                .or_else(|_| Outcome::<u32, u16, ()>::Success(3))
+                .success(),
+            Some(3)
+        );
+        assert_eq!(
+            Outcome::<Option<u32>, u16, ()>::Success(None).transpose(),
+            None
+        );
+        assert_eq!(
+            Outcome::<Result<u32, u16>, u16, ()>::Success(Err(4)).flatten(),
+            Outcome::Failure(4)
+        );
+    }
 }
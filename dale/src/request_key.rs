@@ -0,0 +1,22 @@
+use core::hash::Hash;
+
+/// Derives a key from a request, used by [`Coalesce`](crate::Coalesce) and
+/// [`Cache`](crate::Cache) to tell identical requests apart.
+///
+/// Returning `None` opts the request out.
+pub trait RequestKey<R> {
+    type Key: Hash + Eq + Clone;
+    fn key(&self, req: &R) -> Option<Self::Key>;
+}
+
+impl<R, F, K> RequestKey<R> for F
+where
+    F: Fn(&R) -> Option<K>,
+    K: Hash + Eq + Clone,
+{
+    type Key = K;
+
+    fn key(&self, req: &R) -> Option<K> {
+        (self)(req)
+    }
+}
@@ -134,6 +134,19 @@ pub trait ServiceExt<T>: Service<T> {
         UnpackOne::new(self)
     }
 
+    // Introspection
+
+    #[cfg(feature = "alloc")]
+    fn named(
+        self,
+        name: impl Into<crate::types::alloc::Cow<'static, str>>,
+    ) -> crate::describe::Named<Self>
+    where
+        Self: Sized,
+    {
+        crate::describe::Named::new(self, name)
+    }
+
     // Boxing
 
     #[cfg(feature = "alloc")]
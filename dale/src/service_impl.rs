@@ -0,0 +1,207 @@
+//! `Service` for standard wrappers.
+//!
+//! `&F` and `Box<F>` are already services when `F` is a closure, through the
+//! blanket impl over `Fn`. A general `&S`/`Box<S>` impl would overlap with it,
+//! so share other services with `Arc` or [`ServiceExt::boxed`](crate::ServiceExt::boxed).
+use crate::{IntoOutcome, Outcome, Service};
+use core::{
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use either::Either;
+use futures_core::ready;
+use pin_project_lite::pin_project;
+
+#[cfg(feature = "alloc")]
+impl<T, S> Service<T> for crate::types::alloc::Arc<S>
+where
+    S: Service<T> + ?Sized,
+{
+    type Output = S::Output;
+
+    type Future = S::Future;
+
+    fn call(&self, req: T) -> Self::Future {
+        (**self).call(req)
+    }
+}
+
+/// `None` passes the request on with `Next`.
+impl<T, S> Service<T> for Option<S>
+where
+    S: Service<T>,
+{
+    type Output =
+        Outcome<<S::Output as IntoOutcome<T>>::Success, <S::Output as IntoOutcome<T>>::Failure, T>;
+
+    type Future = OptionServiceFuture<S::Future, T>;
+
+    fn call(&self, req: T) -> Self::Future {
+        let state = match self {
+            Some(service) => OptionState::Call {
+                future: service.call(req),
+            },
+            None => OptionState::Next { req: Some(req) },
+        };
+
+        OptionServiceFuture { state }
+    }
+}
+
+pin_project! {
+    pub struct OptionServiceFuture<F, T> {
+        #[pin]
+        state: OptionState<F, T>,
+    }
+}
+
+pin_project! {
+    #[project = OptionStateProj]
+    enum OptionState<F, T> {
+        Call {
+            #[pin]
+            future: F,
+        },
+        Next {
+            req: Option<T>,
+        },
+    }
+}
+
+impl<F, T> Future for OptionServiceFuture<F, T>
+where
+    F: Future,
+    F::Output: IntoOutcome<T>,
+{
+    type Output =
+        Outcome<<F::Output as IntoOutcome<T>>::Success, <F::Output as IntoOutcome<T>>::Failure, T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project().state.project() {
+            OptionStateProj::Call { future } => Poll::Ready(ready!(future.poll(cx)).into_outcome()),
+            OptionStateProj::Next { req } => {
+                Poll::Ready(Outcome::Next(req.take().expect("poll after done")))
+            }
+        }
+    }
+}
+
+/// Calls whichever arm is present. Use [`ServiceExt::unify`](crate::ServiceExt::unify)
+/// when both arms produce the same types.
+impl<T, L, R> Service<T> for Either<L, R>
+where
+    L: Service<T>,
+    R: Service<T>,
+{
+    type Output = Outcome<
+        Either<<L::Output as IntoOutcome<T>>::Success, <R::Output as IntoOutcome<T>>::Success>,
+        Either<<L::Output as IntoOutcome<T>>::Failure, <R::Output as IntoOutcome<T>>::Failure>,
+        T,
+    >;
+
+    type Future = EitherServiceFuture<L::Future, R::Future, T>;
+
+    fn call(&self, req: T) -> Self::Future {
+        let state = match self {
+            Either::Left(left) => EitherState::Left {
+                future: left.call(req),
+            },
+            Either::Right(right) => EitherState::Right {
+                future: right.call(req),
+            },
+        };
+
+        EitherServiceFuture {
+            state,
+            _t: PhantomData,
+        }
+    }
+}
+
+pin_project! {
+    pub struct EitherServiceFuture<L, R, T> {
+        #[pin]
+        state: EitherState<L, R>,
+        _t: PhantomData<fn() -> T>,
+    }
+}
+
+pin_project! {
+    #[project = EitherStateProj]
+    enum EitherState<L, R> {
+        Left {
+            #[pin]
+            future: L,
+        },
+        Right {
+            #[pin]
+            future: R,
+        },
+    }
+}
+
+impl<L, R, T> Future for EitherServiceFuture<L, R, T>
+where
+    L: Future,
+    L::Output: IntoOutcome<T>,
+    R: Future,
+    R::Output: IntoOutcome<T>,
+{
+    type Output = Outcome<
+        Either<<L::Output as IntoOutcome<T>>::Success, <R::Output as IntoOutcome<T>>::Success>,
+        Either<<L::Output as IntoOutcome<T>>::Failure, <R::Output as IntoOutcome<T>>::Failure>,
+        T,
+    >;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let outcome = match self.project().state.project() {
+            EitherStateProj::Left { future } => match ready!(future.poll(cx)).into_outcome() {
+                Outcome::Success(ret) => Outcome::Success(Either::Left(ret)),
+                Outcome::Failure(err) => Outcome::Failure(Either::Left(err)),
+                Outcome::Next(next) => Outcome::Next(next),
+            },
+            EitherStateProj::Right { future } => match ready!(future.poll(cx)).into_outcome() {
+                Outcome::Success(ret) => Outcome::Success(Either::Right(ret)),
+                Outcome::Failure(err) => Outcome::Failure(Either::Right(err)),
+                Outcome::Next(next) => Outcome::Next(next),
+            },
+        };
+
+        Poll::Ready(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_executor::block_on;
+
+    async fn double(n: u32) -> Result<u32, ()> {
+        Ok(n * 2)
+    }
+
+    async fn negate(n: u32) -> Result<i64, ()> {
+        Ok(-(n as i64))
+    }
+
+    #[test]
+    fn test_option() {
+        let service = Some(double);
+        assert_eq!(block_on(service.call(2)), Outcome::Success(4));
+
+        let service = if false { Some(double) } else { None };
+        assert_eq!(block_on(service.call(2)), Outcome::Next(2));
+    }
+
+    #[test]
+    fn test_either() {
+        let service = if true {
+            Either::Left(double)
+        } else {
+            Either::Right(negate)
+        };
+        assert_eq!(block_on(service.call(2)), Outcome::Success(Either::Left(4)));
+    }
+}
@@ -0,0 +1,318 @@
+use crate::{types::alloc::*, IntoOutcome, Outcome, Service};
+use core::{
+    future::Future,
+    hash::{Hash, Hasher},
+    pin::Pin,
+    sync::atomic::{AtomicU32, AtomicUsize, Ordering},
+    task::{Context, Poll},
+};
+use futures_core::ready;
+use pin_project_lite::pin_project;
+
+/// Route each request to one of `services`, chosen by weight.
+pub fn split<S>(services: Vec<(u32, S)>) -> Split<S, NoKey> {
+    let (weights, services): (Vec<_>, Vec<_>) = services
+        .into_iter()
+        .map(|(weight, service)| (AtomicU32::new(weight), service))
+        .unzip();
+
+    Split {
+        services: Arc::new(services),
+        weights: Arc::new(weights),
+        counter: Arc::new(AtomicUsize::new(0)),
+        key: NoKey,
+    }
+}
+
+/// Extracts a stickiness key from a request.
+///
+/// Requests with the same key are always sent to the same service, as long as
+/// the weights stay the same.
+pub trait SplitKey<R> {
+    fn key(&self, req: &R) -> Option<u64>;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NoKey;
+
+impl<R> SplitKey<R> for NoKey {
+    fn key(&self, _req: &R) -> Option<u64> {
+        None
+    }
+}
+
+impl<R, F, K> SplitKey<R> for F
+where
+    F: Fn(&R) -> Option<K>,
+    K: Hash,
+{
+    fn key(&self, req: &R) -> Option<u64> {
+        let key = (self)(req)?;
+        let mut hasher = Fnv::default();
+        key.hash(&mut hasher);
+        Some(hasher.finish())
+    }
+}
+
+#[derive(Debug)]
+pub struct Split<S, K> {
+    services: Arc<Vec<S>>,
+    weights: Arc<Vec<AtomicU32>>,
+    counter: Arc<AtomicUsize>,
+    key: K,
+}
+
+impl<S, K: Clone> Clone for Split<S, K> {
+    fn clone(&self) -> Self {
+        Split {
+            services: self.services.clone(),
+            weights: self.weights.clone(),
+            counter: self.counter.clone(),
+            key: self.key.clone(),
+        }
+    }
+}
+
+impl<S, K> Split<S, K> {
+    /// Make assignment sticky, keyed by `key`. Requests without a key are
+    /// distributed by weight as usual.
+    pub fn sticky<F>(self, key: F) -> Split<S, F> {
+        Split {
+            services: self.services,
+            weights: self.weights,
+            counter: self.counter,
+            key,
+        }
+    }
+
+    /// A handle for adjusting the weights at runtime.
+    pub fn handle(&self) -> SplitHandle {
+        SplitHandle {
+            weights: self.weights.clone(),
+        }
+    }
+
+    fn pick(&self, key: Option<u64>) -> Option<usize> {
+        loop {
+            let total: u64 = self
+                .weights
+                .iter()
+                .map(|w| w.load(Ordering::Relaxed) as u64)
+                .sum();
+
+            if total == 0 {
+                return None;
+            }
+
+            let mut point = match key {
+                Some(key) => key % total,
+                None => splitmix(self.counter.fetch_add(1, Ordering::Relaxed) as u64) % total,
+            };
+
+            for (idx, weight) in self.weights.iter().enumerate() {
+                let weight = weight.load(Ordering::Relaxed) as u64;
+                if point < weight {
+                    return Some(idx);
+                }
+                point -= weight;
+            }
+
+            // The weights changed underneath us, try again
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SplitHandle {
+    weights: Arc<Vec<AtomicU32>>,
+}
+
+impl SplitHandle {
+    pub fn len(&self) -> usize {
+        self.weights.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.weights.is_empty()
+    }
+
+    pub fn weight(&self, idx: usize) -> Option<u32> {
+        self.weights.get(idx).map(|w| w.load(Ordering::Relaxed))
+    }
+
+    /// Set the weight of the service at `idx`. Returns false if out of range.
+    pub fn set_weight(&self, idx: usize, weight: u32) -> bool {
+        match self.weights.get(idx) {
+            Some(w) => {
+                w.store(weight, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn weights(&self) -> Vec<u32> {
+        self.weights
+            .iter()
+            .map(|w| w.load(Ordering::Relaxed))
+            .collect()
+    }
+}
+
+impl<S, K, R> Service<R> for Split<S, K>
+where
+    S: Service<R>,
+    K: SplitKey<R>,
+{
+    type Output =
+        Outcome<<S::Output as IntoOutcome<R>>::Success, <S::Output as IntoOutcome<R>>::Failure, R>;
+
+    type Future = SplitFuture<S, R>;
+
+    fn call(&self, req: R) -> Self::Future {
+        let state = match self.pick(self.key.key(&req)) {
+            Some(idx) => State::Call {
+                future: self.services[idx].call(req),
+            },
+            None => State::Empty { req: Some(req) },
+        };
+
+        SplitFuture { state }
+    }
+}
+
+pin_project! {
+    pub struct SplitFuture<S, R> where S: Service<R> {
+        #[pin]
+        state: State<S, R>,
+    }
+}
+
+pin_project! {
+    #[project = StateProj]
+    enum State<S, R> where S: Service<R> {
+        Call {
+            #[pin]
+            future: S::Future,
+        },
+        Empty {
+            req: Option<R>,
+        },
+    }
+}
+
+impl<S, R> Future for SplitFuture<S, R>
+where
+    S: Service<R>,
+{
+    type Output =
+        Outcome<<S::Output as IntoOutcome<R>>::Success, <S::Output as IntoOutcome<R>>::Failure, R>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project().state.project() {
+            StateProj::Call { future } => Poll::Ready(ready!(future.poll(cx)).into_outcome()),
+            StateProj::Empty { req } => {
+                Poll::Ready(Outcome::Next(req.take().expect("poll after done")))
+            }
+        }
+    }
+}
+
+impl<S, K> crate::Describe for Split<S, K>
+where
+    S: crate::Describe,
+{
+    fn describe(&self) -> crate::Description {
+        crate::Description::new("split").with_children(
+            self.services
+                .iter()
+                .zip(self.weights.iter())
+                .map(|(service, weight)| {
+                    crate::Description::new("weight")
+                        .with_detail(weight.load(Ordering::Relaxed))
+                        .with_child(service.describe())
+                }),
+        )
+    }
+}
+
+fn splitmix(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+// FNV-1a, so sticky keys hash the same across builds and restarts
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::convert::Infallible;
+    use futures_executor::block_on;
+
+    fn service(idx: usize) -> impl Service<u32, Output = Outcome<usize, Infallible, u32>> + Clone {
+        move |_req: u32| async move { Outcome::Success(idx) }
+    }
+
+    #[test]
+    fn test_weights() {
+        let split = split(vec![(1, service(0)), (0, service(1)), (3, service(2))]);
+
+        let mut hits = [0; 3];
+        for i in 0..400 {
+            if let Outcome::Success(idx) = block_on(split.call(i)) {
+                hits[idx] += 1;
+            }
+        }
+
+        assert_eq!(hits[1], 0);
+        assert!(hits[2] > hits[0] * 2, "{:?}", hits);
+    }
+
+    #[test]
+    fn test_sticky() {
+        let split = split(vec![(1, service(0)), (1, service(1))]).sticky(|req: &u32| Some(*req));
+
+        for i in 0..20 {
+            let first = block_on(split.call(i));
+            for _ in 0..5 {
+                assert_eq!(block_on(split.call(i)), first);
+            }
+        }
+    }
+
+    #[test]
+    fn test_handle() {
+        let split = split(vec![(1, service(0)), (1, service(1))]);
+        let handle = split.handle();
+
+        assert!(handle.set_weight(0, 0));
+        assert!(!handle.set_weight(2, 1));
+        assert_eq!(handle.weights(), vec![0, 1]);
+        assert_eq!(block_on(split.call(0)), Outcome::Success(1));
+
+        handle.set_weight(1, 0);
+        assert_eq!(block_on(split.call(7)), Outcome::Next(7));
+    }
+}
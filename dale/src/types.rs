@@ -16,7 +16,7 @@ where
 #[cfg(feature = "alloc")]
 pub mod alloc {
     #[cfg(not(feature = "std"))]
-    pub use alloc::{sync::Arc, vec::Vec};
+    pub use alloc::{borrow::Cow, boxed::Box, string::String, sync::Arc, vec::Vec};
     #[cfg(feature = "std")]
-    pub use std::{sync::Arc, vec::Vec};
+    pub use std::{borrow::Cow, boxed::Box, string::String, sync::Arc, vec::Vec};
 }